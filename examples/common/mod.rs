@@ -1,12 +1,11 @@
 use atat::asynch::Client;
-use atat::{AtatIngress, Buffers, Config, DefaultDigester, Ingress};
+use atat::{AtatIngress, Buffers, Config};
 use embedded_io_adapters::tokio_1::FromTokio;
-use tokio::io::split;
 use tokio_serial::SerialPortBuilderExt;
 use tokio_serial::SerialStream;
-use wurth_calypso::command;
+use wurth_calypso::{command, CalypsoDigester};
 
-const INGRESS_BUF_SIZE: usize = 1024;
+const INGRESS_BUF_SIZE: usize = CalypsoDigester::MIN_INGRESS_BUF_SIZE;
 const URC_CAPACITY: usize = 128;
 const URC_SUBSCRIBERS: usize = 3;
 
@@ -39,9 +38,9 @@ pub async fn atat_client(
         URC_SUBSCRIBERS,
     > = Buffers::<command::Urc, INGRESS_BUF_SIZE, URC_CAPACITY, URC_SUBSCRIBERS>::new();
 
-    let (mut ingress, mut client) = BUFFERS.split(
+    let (mut ingress, client) = BUFFERS.split(
         FromTokio::new(writer),
-        DefaultDigester::<command::Urc>::default(),
+        CalypsoDigester::new(),
         Config::default(),
     );
 
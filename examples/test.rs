@@ -1,9 +1,6 @@
-#![feature(async_fn_in_trait)]
-#![allow(incomplete_features)]
-
 mod common;
 
-use embassy_time; // this stops a linker error cause embassy is dumb
+use embassy_time as _; // this stops a linker error cause embassy is dumb
 use std::env;
 use std::time::Duration;
 use wurth_calypso::Calypso;
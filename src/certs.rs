@@ -0,0 +1,382 @@
+//! Certificate and key store.
+//!
+//! Certificates and keys are regular files in the module file system. A
+//! [`CertificateFile`] names one of them, so connections can refer to stored
+//! files without passing raw strings around.
+
+use crate::command::file::{FileEntry, OpenFlags, ABORT_SIGNATURE};
+use crate::command::{DataFormat, Escaped, Payload};
+use crate::{
+    command, Calypso, Crc32, Error, Transfer, TransferOptions,
+    MAX_FILE_NAME_LENGTH, MAX_PAYLOAD_SIZE,
+};
+use atat::asynch::AtatClient;
+use heapless::{String, Vec};
+
+/// Suffix of the temporary file used while replacing a certificate.
+const TEMP_SUFFIX: &str = ".tmp";
+
+/// File name extensions of certificates and keys.
+const EXTENSIONS: [&str; 5] = [".der", ".pem", ".crt", ".cer", ".key"];
+
+/// A certificate or key stored in the module file system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CertificateFile<'a> {
+    name: &'a str,
+}
+
+impl<'a> CertificateFile<'a> {
+    /// CA certificate used to validate the server of an enterprise network
+    pub const ENTERPRISE_CA: CertificateFile<'static> =
+        CertificateFile::new("/sys/cert/ca.der");
+    /// Client certificate presented to an enterprise network
+    pub const ENTERPRISE_CLIENT: CertificateFile<'static> =
+        CertificateFile::new("/sys/cert/client.der");
+    /// Private key of the enterprise client certificate
+    pub const ENTERPRISE_PRIVATE_KEY: CertificateFile<'static> =
+        CertificateFile::new("/sys/cert/private.key");
+
+    /// Refer to the certificate or key stored under `name`.
+    pub const fn new(name: &'a str) -> Self {
+        Self { name }
+    }
+
+    /// File name in the module file system.
+    pub const fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns true if `name` looks like a certificate or key.
+    ///
+    /// Matches files below a `cert` directory and files with a certificate
+    /// or key extension.
+    pub fn is_certificate(name: &str) -> bool {
+        name.starts_with("/cert/")
+            || name.starts_with("/sys/cert/")
+            || EXTENSIONS.iter().any(|extension| {
+                let name = name.as_bytes();
+                name.len() >= extension.len()
+                    && name[name.len() - extension.len()..]
+                        .eq_ignore_ascii_case(extension.as_bytes())
+            })
+    }
+}
+
+impl<'a> From<&'a str> for CertificateFile<'a> {
+    fn from(name: &'a str) -> Self {
+        Self::new(name)
+    }
+}
+
+/// Store a certificate, replacing an existing file.
+///
+/// The data is first uploaded to a temporary file and verified. The target
+/// is then rewritten from the temporary file as a fail-safe file, so a failed
+/// replacement leaves the previous contents in place.
+pub(crate) async fn store<C: AtatClient>(
+    calypso: &mut Calypso<C>,
+    file: CertificateFile<'_>,
+    data: &[u8],
+) -> Result<Transfer, Error> {
+    let mut temp = String::<{ MAX_FILE_NAME_LENGTH as usize }>::new();
+    temp.push_str(file.name())
+        .and_then(|_| temp.push_str(TEMP_SUFFIX))
+        .map_err(|_| Error::ArgumentTooLong {
+            length: file.name().len() + TEMP_SUFFIX.len(),
+            max: MAX_FILE_NAME_LENGTH.into(),
+        })?;
+
+    let options = TransferOptions {
+        verify: true,
+        ..TransferOptions::default()
+    };
+    let mut reader = data;
+    let uploaded = match calypso
+        .file_upload(&temp, data.len() as u32, &mut reader, &options, |_| {})
+        .await
+    {
+        Ok(uploaded) => uploaded,
+        Err(error) => {
+            calypso.file_delete(&temp, 0).await.ok();
+            return Err(error);
+        }
+    };
+
+    let copied = copy(calypso, &temp, file.name(), &uploaded).await;
+    calypso.file_delete(&temp, 0).await?;
+
+    copied
+}
+
+/// Copy the file `from` into a fail-safe file `to`.
+///
+/// The target is only committed if the copied data matches `expected`.
+async fn copy<C: AtatClient>(
+    calypso: &mut Calypso<C>,
+    from: &str,
+    to: &str,
+    expected: &Transfer,
+) -> Result<Transfer, Error> {
+    let source = calypso
+        .client
+        .send(&command::file::Open {
+            name: Escaped::new(from, MAX_FILE_NAME_LENGTH.into())?,
+            options: OpenFlags::READ.into(),
+            size: 0,
+        })
+        .await?;
+
+    let flags = OpenFlags::CREATE | OpenFlags::OVERWRITE | OpenFlags::FAILSAFE;
+    let target = match calypso
+        .client
+        .send(&command::file::Open {
+            name: Escaped::new(to, MAX_FILE_NAME_LENGTH.into())?,
+            options: flags.into(),
+            size: expected.size,
+        })
+        .await
+    {
+        Ok(target) => target,
+        Err(error) => {
+            close(calypso, source.id, "").await.ok();
+            return Err(error.into());
+        }
+    };
+
+    let result = copy_chunks(calypso, source.id, target.id, expected.size)
+        .await
+        .and_then(|copied| {
+            if copied.crc != expected.crc {
+                return Err(Error::CrcMismatch {
+                    expected: expected.crc,
+                    actual: copied.crc,
+                });
+            }
+            Ok(copied)
+        });
+    let closed_source = close(calypso, source.id, "").await;
+    // roll back the fail-safe target so it keeps its previous contents
+    let signature = if result.is_ok() { "" } else { ABORT_SIGNATURE };
+    let closed_target = close(calypso, target.id, signature).await;
+
+    let transfer = result?;
+    closed_source?;
+    closed_target?;
+
+    Ok(transfer)
+}
+
+async fn copy_chunks<C: AtatClient>(
+    calypso: &mut Calypso<C>,
+    source: u32,
+    target: u32,
+    size: u32,
+) -> Result<Transfer, Error> {
+    let mut crc = Crc32::new();
+    let mut offset = 0;
+
+    while offset < size {
+        let length = (size - offset).min(MAX_PAYLOAD_SIZE.into()) as u16;
+        let chunk = calypso
+            .client
+            .send(&command::file::Read {
+                id: source,
+                offset,
+                format: DataFormat::Binary,
+                length,
+            })
+            .await?;
+        if chunk.data.is_empty() {
+            break;
+        }
+
+        let written = calypso
+            .client
+            .send(&command::file::Write {
+                id: target,
+                offset,
+                format: DataFormat::Binary,
+                length: chunk.data.len() as u16,
+                data: Payload(&chunk.data),
+            })
+            .await?;
+        if usize::from(written.length) != chunk.data.len() {
+            return Err(Error::SizeMismatch {
+                expected: offset + chunk.data.len() as u32,
+                actual: offset + u32::from(written.length),
+            });
+        }
+
+        crc.update(&chunk.data);
+        offset += chunk.data.len() as u32;
+    }
+
+    if offset != size {
+        return Err(Error::SizeMismatch {
+            expected: size,
+            actual: offset,
+        });
+    }
+
+    Ok(Transfer {
+        size,
+        crc: crc.finish(),
+    })
+}
+
+async fn close<C: AtatClient>(
+    calypso: &mut Calypso<C>,
+    id: u32,
+    signature: &str,
+) -> Result<(), Error> {
+    calypso
+        .client
+        .send(&command::file::Close {
+            id,
+            certificate: Escaped::default(),
+            signature: signature.into(),
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// List the stored certificates and keys.
+pub(crate) async fn list<C: AtatClient, const N: usize>(
+    calypso: &mut Calypso<C>,
+) -> Result<Vec<FileEntry, N>, Error> {
+    let mut files = calypso.file_list::<N>().await?;
+    files.retain(|entry| CertificateFile::is_certificate(&entry.name));
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{block_on, MockClient};
+
+    #[test]
+    fn failed_copy_aborts_target() {
+        let client = MockClient::default()
+            .respond(b"+fileopen:1,0")
+            .respond(b"+fileopen:2,0")
+            .fail(atat::Error::Timeout)
+            .respond(b"")
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+
+        let expected = Transfer { size: 16, crc: 0 };
+        let result = block_on(copy(&mut calypso, "/a.tmp", "/a", &expected));
+
+        assert!(matches!(result, Err(Error::At(atat::Error::Timeout))));
+        let sent = &calypso.client.sent;
+        assert_eq!(sent[3], "AT+fileClose=1,,\r\n");
+        assert_eq!(sent[4], "AT+fileClose=2,,A\r\n");
+    }
+
+    #[test]
+    fn copy_commits_target() {
+        let client = MockClient::default()
+            .respond(b"+fileopen:1,0")
+            .respond(b"+fileopen:2,0")
+            .respond(b"+fileread:0,3,abc")
+            .respond(b"+filewrite:3")
+            .respond(b"")
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+
+        let expected = Transfer {
+            size: 3,
+            crc: 0x3524_41c2,
+        };
+        let transfer =
+            block_on(copy(&mut calypso, "/a.tmp", "/a", &expected)).unwrap();
+
+        assert_eq!(transfer, expected);
+        assert_eq!(calypso.client.sent[5], "AT+fileClose=2,,\r\n");
+    }
+
+    #[test]
+    fn corrupted_copy_aborts_target() {
+        let client = MockClient::default()
+            .respond(b"+fileopen:1,0")
+            .respond(b"+fileopen:2,0")
+            .respond(b"+fileread:0,3,abd")
+            .respond(b"+filewrite:3")
+            .respond(b"")
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+
+        let expected = Transfer {
+            size: 3,
+            crc: 0x3524_41c2,
+        };
+        let result = block_on(copy(&mut calypso, "/a.tmp", "/a", &expected));
+
+        assert!(matches!(result, Err(Error::CrcMismatch { .. })));
+        assert_eq!(calypso.client.sent[5], "AT+fileClose=2,,A\r\n");
+    }
+
+    #[test]
+    fn short_copy_aborts_target() {
+        let client = MockClient::default()
+            .respond(b"+fileopen:1,0")
+            .respond(b"+fileopen:2,0")
+            .respond(b"+fileread:0,0,")
+            .respond(b"")
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+
+        let expected = Transfer { size: 3, crc: 0 };
+        let result = block_on(copy(&mut calypso, "/a.tmp", "/a", &expected));
+
+        assert_eq!(
+            result,
+            Err(Error::SizeMismatch {
+                expected: 3,
+                actual: 0
+            })
+        );
+        assert_eq!(calypso.client.sent[4], "AT+fileClose=2,,A\r\n");
+    }
+
+    #[test]
+    fn failed_upload_deletes_temp() {
+        let client = MockClient::default()
+            .fail(atat::Error::Timeout)
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+
+        let result = block_on(store(&mut calypso, "/cert/a.der".into(), b"a"));
+
+        assert_eq!(result, Err(Error::At(atat::Error::Timeout)));
+        assert_eq!(calypso.client.sent[1], "AT+fileDel=/cert/a.der.tmp,0\r\n");
+    }
+
+    #[test]
+    fn long_names() {
+        let name = "a".repeat(MAX_FILE_NAME_LENGTH.into());
+        let mut calypso = Calypso::new(MockClient::default());
+
+        let result = block_on(store(&mut calypso, name.as_str().into(), b""));
+
+        assert_eq!(
+            result,
+            Err(Error::ArgumentTooLong {
+                length: 184,
+                max: 180
+            })
+        );
+        assert!(calypso.client.sent.is_empty());
+    }
+
+    #[test]
+    fn certificate_names() {
+        assert!(CertificateFile::is_certificate("/cert/root"));
+        assert!(CertificateFile::is_certificate("client.PEM"));
+        assert!(!CertificateFile::is_certificate("notes.txt"));
+        assert!(!CertificateFile::is_certificate("ä"));
+        assert!(!CertificateFile::is_certificate("zertifikat.dä"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Helpers for parsing responses that carry raw data.
+//!
+//! `serde_at` stops at the first comma of a field, so responses that end in
+//! an arbitrary data payload are parsed by hand using these helpers.
+
+use atat::Error;
+use core::str::{from_utf8, FromStr};
+
+/// Comma separated arguments of a single response line.
+pub(crate) struct Args<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Args<'a> {
+    /// Creates a new argument parser, skipping the `+command:` prefix.
+    pub fn new(resp: &'a [u8]) -> Self {
+        let start = resp
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(resp.len());
+        let buf = &resp[start..];
+
+        let buf = match buf.first() {
+            Some(b'+') => match buf.iter().position(|&b| b == b':') {
+                Some(index) => &buf[index + 1..],
+                None => &buf[buf.len()..],
+            },
+            _ => buf,
+        };
+
+        Self { buf }
+    }
+
+    /// Next argument as raw bytes.
+    pub fn next_bytes(&mut self) -> Result<&'a [u8], Error> {
+        if self.buf.is_empty() {
+            return Err(Error::Parse);
+        }
+
+        let end = self
+            .buf
+            .iter()
+            .position(|&b| b == b',')
+            .unwrap_or(self.buf.len());
+        let arg = &self.buf[..end];
+        self.buf = &self.buf[(end + 1).min(self.buf.len())..];
+
+        Ok(arg)
+    }
+
+    /// Next argument, which may be enclosed in double quotes.
+    ///
+    /// Separators inside the quotes belong to the argument. The quotes are
+    /// removed, backslash escapes are kept.
+    pub fn next_quoted(&mut self) -> Result<&'a [u8], Error> {
+        if self.buf.first() != Some(&b'"') {
+            return self.next_bytes();
+        }
+
+        let mut escaped = false;
+        let end = self.buf[1..]
+            .iter()
+            .position(|&b| {
+                let quote = b == b'"' && !escaped;
+                escaped = !escaped && b == b'\\';
+                quote
+            })
+            .ok_or(Error::Parse)?
+            + 1;
+        let arg = &self.buf[1..end];
+
+        self.buf = match &self.buf[end + 1..] {
+            [] => &[],
+            [b',', rest @ ..] => rest,
+            _ => return Err(Error::Parse),
+        };
+
+        Ok(arg)
+    }
+
+    /// Next argument as a string.
+    pub fn next_str(&mut self) -> Result<&'a str, Error> {
+        from_utf8(self.next_bytes()?)
+            .map(str::trim)
+            .map_err(|_| Error::Parse)
+    }
+
+    /// Next argument parsed into a value.
+    pub fn next<T: FromStr>(&mut self) -> Result<T, Error> {
+        self.next_str()?.parse().map_err(|_| Error::Parse)
+    }
+
+    /// Remaining arguments as a single string.
+    pub fn rest(&mut self) -> Result<&'a str, Error> {
+        let rest = from_utf8(self.buf).map_err(|_| Error::Parse)?;
+        self.buf = &self.buf[self.buf.len()..];
+
+        Ok(rest.trim())
+    }
+
+    /// Raw data of the given length.
+    pub fn data(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        let data = self.buf.get(..length).ok_or(Error::Parse)?;
+        self.buf = &self.buf[length..];
+
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments() {
+        let mut args = Args::new(b"\r\n+get: 12, name ,-3");
+
+        assert_eq!(args.next::<u8>(), Ok(12));
+        assert_eq!(args.next_str(), Ok("name"));
+        assert_eq!(args.next::<i32>(), Ok(-3));
+        assert_eq!(args.next_bytes(), Err(Error::Parse));
+    }
+
+    #[test]
+    fn without_prefix() {
+        let mut args = Args::new(b"connect,home");
+
+        assert_eq!(args.next_str(), Ok("connect"));
+        assert_eq!(args.rest(), Ok("home"));
+        assert_eq!(args.rest(), Ok(""));
+    }
+
+    #[test]
+    fn empty_arguments() {
+        let mut args = Args::new(b"+x:,1");
+
+        assert_eq!(args.next_str(), Ok(""));
+        assert_eq!(args.next::<u8>(), Ok(1));
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        let mut args = Args::new(b"+x:abc,300");
+
+        assert_eq!(args.next::<u8>(), Err(Error::Parse));
+        assert_eq!(args.next::<u8>(), Err(Error::Parse));
+    }
+
+    #[test]
+    fn data_with_separators() {
+        let mut args = Args::new(b"+recv:1,5,a,b\r\nc");
+
+        assert_eq!(args.next::<u8>(), Ok(1));
+        let length = args.next().unwrap();
+        assert_eq!(args.data(length), Ok(&b"a,b\r\n"[..]));
+        assert_eq!(args.data(1), Ok(&b"c"[..]));
+        assert_eq!(args.data(1), Err(Error::Parse));
+    }
+
+    #[test]
+    fn quoted_arguments() {
+        let mut args = Args::new(br#"+x:"a,\"b\"",c,"d""#);
+
+        assert_eq!(args.next_quoted(), Ok(&br#"a,\"b\""#[..]));
+        assert_eq!(args.next_quoted(), Ok(&b"c"[..]));
+        assert_eq!(args.next_quoted(), Ok(&b"d"[..]));
+        assert_eq!(Args::new(br#"+x:"a"b"#).next_quoted(), Err(Error::Parse));
+        assert_eq!(Args::new(br#"+x:"a"#).next_quoted(), Err(Error::Parse));
+    }
+}
@@ -1,6 +1,7 @@
-use super::EmptyResponse;
-use atat::atat_derive::AtatCmd;
-use heapless::String;
+use super::{args::Args, EmptyResponse};
+use atat::atat_derive::{AtatCmd, AtatResp};
+use atat::{AtatCmd, Error, InternalError};
+use heapless::{String, Vec};
 
 #[derive(AtatCmd)]
 #[at_cmd("+start", EmptyResponse, timeout_ms = 100)]
@@ -61,3 +62,211 @@ pub struct Set {
     #[at_arg(position = 1)]
     pub option: String<24>,
 }
+
+/// Firmware and hardware versions of the module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    /// Chip identifier
+    pub chip_id: u32,
+    /// MAC firmware version
+    pub mac: String<16>,
+    /// PHY firmware version
+    pub phy: String<16>,
+    /// Network processor firmware version
+    pub nwp: String<16>,
+    /// ROM version
+    pub rom: String<16>,
+    /// Calypso firmware version
+    pub firmware: String<16>,
+}
+
+impl atat::AtatResp for Version {}
+
+/// Read the module versions.
+#[derive(Debug, Clone)]
+pub struct GetVersion;
+
+impl AtatCmd<32> for GetVersion {
+    type Response = Version;
+
+    const MAX_TIMEOUT_MS: u32 = 100;
+
+    fn as_bytes(&self) -> Vec<u8, 32> {
+        Vec::from_slice(b"AT+get=general,version\r\n").unwrap()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+        let chip_id = args.next()?;
+
+        Ok(Version {
+            chip_id,
+            mac: args.next()?,
+            phy: args.next()?,
+            nwp: args.next()?,
+            rom: args.next()?,
+            firmware: args.next()?,
+        })
+    }
+}
+
+/// Date and time as kept by the module clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatResp)]
+pub struct DateTime {
+    /// Hour (0-23)
+    #[at_arg(position = 0)]
+    pub hour: u8,
+    /// Minute (0-59)
+    #[at_arg(position = 1)]
+    pub minute: u8,
+    /// Second (0-59)
+    #[at_arg(position = 2)]
+    pub second: u8,
+    /// Day of the month (1-31)
+    #[at_arg(position = 3)]
+    pub day: u8,
+    /// Month of the year (1-12)
+    #[at_arg(position = 4)]
+    pub month: u8,
+    /// Year (e.g. 2024)
+    #[at_arg(position = 5)]
+    pub year: u16,
+}
+
+/// Read the module clock.
+#[derive(AtatCmd)]
+#[at_cmd("+get=general,time", DateTime, timeout_ms = 100)]
+pub struct GetTime {}
+
+/// Set the module clock.
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+set=general,time,",
+    EmptyResponse,
+    timeout_ms = 100,
+    value_sep = false
+)]
+pub struct SetTime {
+    #[at_arg(position = 0)]
+    pub hour: u8,
+    #[at_arg(position = 1)]
+    pub minute: u8,
+    #[at_arg(position = 2)]
+    pub second: u8,
+    #[at_arg(position = 3)]
+    pub day: u8,
+    #[at_arg(position = 4)]
+    pub month: u8,
+    #[at_arg(position = 5)]
+    pub year: u16,
+}
+
+impl From<DateTime> for SetTime {
+    fn from(value: DateTime) -> Self {
+        Self {
+            hour: value.hour,
+            minute: value.minute,
+            second: value.second,
+            day: value.day,
+            month: value.month,
+            year: value.year,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<DateTime> for chrono::NaiveDateTime {
+    type Error = ();
+
+    fn try_from(value: DateTime) -> Result<Self, Self::Error> {
+        let date = chrono::NaiveDate::from_ymd_opt(
+            value.year.into(),
+            value.month.into(),
+            value.day.into(),
+        )
+        .ok_or(())?;
+        let time = chrono::NaiveTime::from_hms_opt(
+            value.hour.into(),
+            value.minute.into(),
+            value.second.into(),
+        )
+        .ok_or(())?;
+
+        Ok(chrono::NaiveDateTime::new(date, time))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDateTime> for DateTime {
+    type Error = ();
+
+    fn try_from(value: chrono::NaiveDateTime) -> Result<Self, Self::Error> {
+        use chrono::{Datelike, Timelike};
+
+        Ok(Self {
+            hour: value.hour() as u8,
+            minute: value.minute() as u8,
+            second: value.second() as u8,
+            day: value.day() as u8,
+            month: value.month() as u8,
+            year: value.year().try_into().map_err(|_| ())?,
+        })
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<DateTime> for time::PrimitiveDateTime {
+    type Error = ();
+
+    fn try_from(value: DateTime) -> Result<Self, Self::Error> {
+        let month = time::Month::try_from(value.month).map_err(|_| ())?;
+        let date =
+            time::Date::from_calendar_date(value.year.into(), month, value.day)
+                .map_err(|_| ())?;
+        let time = time::Time::from_hms(value.hour, value.minute, value.second)
+            .map_err(|_| ())?;
+
+        Ok(time::PrimitiveDateTime::new(date, time))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::PrimitiveDateTime> for DateTime {
+    type Error = ();
+
+    fn try_from(value: time::PrimitiveDateTime) -> Result<Self, Self::Error> {
+        Ok(Self {
+            hour: value.hour(),
+            minute: value.minute(),
+            second: value.second(),
+            day: value.day(),
+            month: value.month().into(),
+            year: value.year().try_into().map_err(|_| ())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version() {
+        let resp = b"+get:4,31.2.0.0.0,31.2.0.0.0,3.7.0.1,0.0.0.0,1.9.0";
+        let version = GetVersion.parse(Ok(resp)).unwrap();
+
+        assert_eq!(version.chip_id, 4);
+        assert_eq!(version.nwp, "3.7.0.1");
+        assert_eq!(version.firmware, "1.9.0");
+    }
+
+    #[test]
+    fn version_rejects_long_fields() {
+        let resp = b"+get:4,31.2.0.0.0.0.0.0.0.0.0.0,1,2,3,4";
+
+        assert!(matches!(GetVersion.parse(Ok(resp)), Err(Error::Parse)));
+    }
+}
@@ -0,0 +1,464 @@
+use super::{args::Args, DataFormat, EmptyResponse, Escaped, Payload};
+use crate::{MAX_FILE_NAME_LENGTH, MAX_PAYLOAD_SIZE};
+use atat::atat_derive::{AtatCmd, AtatResp};
+use atat::{AtatCmd, Error, InternalError};
+use core::fmt::Write as _;
+use core::ops::BitOr;
+use heapless::{String, Vec};
+
+/// Options used when opening a file.
+///
+/// Options are combined with `|`, e.g.
+/// `OpenFlags::CREATE | OpenFlags::FAILSAFE`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpenFlags(u16);
+
+impl OpenFlags {
+    /// Open for reading
+    pub const READ: Self = Self(1 << 0);
+    /// Open for writing
+    pub const WRITE: Self = Self(1 << 1);
+    /// Create the file if it does not exist
+    pub const CREATE: Self = Self(1 << 2);
+    /// Replace the contents of an existing file
+    pub const OVERWRITE: Self = Self(1 << 3);
+    /// Keep a copy of the previous contents until the file is closed
+    pub const FAILSAFE: Self = Self(1 << 4);
+    /// Encrypt the file and protect it with a secure token
+    pub const SECURE: Self = Self(1 << 5);
+    /// Do not require a signature when closing a secure file
+    pub const NO_SIGNATURE_TEST: Self = Self(1 << 6);
+    /// Use a static instead of a random secure token
+    pub const STATIC_TOKEN: Self = Self(1 << 7);
+    /// Use the vendor token as secure token
+    pub const VENDOR_TOKEN: Self = Self(1 << 8);
+    /// Allow writing a secure file without a token
+    pub const PUBLIC_WRITE: Self = Self(1 << 9);
+    /// Allow reading a secure file without a token
+    pub const PUBLIC_READ: Self = Self(1 << 10);
+
+    const NAMES: [(Self, &'static str); 11] = [
+        (Self::READ, "READ"),
+        (Self::WRITE, "WRITE"),
+        (Self::CREATE, "CREATE"),
+        (Self::OVERWRITE, "OVERWRITE"),
+        (Self::FAILSAFE, "CREATE_FAILSAFE"),
+        (Self::SECURE, "CREATE_SECURE"),
+        (Self::NO_SIGNATURE_TEST, "CREATE_NOSIGNATURE"),
+        (Self::STATIC_TOKEN, "CREATE_STATIC_TOKEN"),
+        (Self::VENDOR_TOKEN, "CREATE_VENDOR_TOKEN"),
+        (Self::PUBLIC_WRITE, "CREATE_PUBLIC_WRITE"),
+        (Self::PUBLIC_READ, "CREATE_PUBLIC_READ"),
+    ];
+
+    /// Returns true if all options in `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for OpenFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl From<OpenFlags> for String<128> {
+    fn from(value: OpenFlags) -> Self {
+        let mut options = String::new();
+        for (flag, name) in OpenFlags::NAMES {
+            if value.contains(flag) {
+                if !options.is_empty() {
+                    options.push('|').ok();
+                }
+                options.push_str(name).ok();
+            }
+        }
+        options
+    }
+}
+
+/// Properties of a stored file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileFlags(u16);
+
+impl FileFlags {
+    /// File is open for writing
+    pub const OPEN_WRITE: Self = Self(1 << 0);
+    /// Last open for writing failed
+    pub const OPEN_ERROR: Self = Self(1 << 1);
+    /// File has to be committed
+    pub const MUST_COMMIT: Self = Self(1 << 2);
+    /// File is part of a bundle
+    pub const BUNDLE_FILE: Self = Self(1 << 3);
+    /// File is waiting to be committed
+    pub const PENDING_COMMIT: Self = Self(1 << 4);
+    /// Bundle is waiting to be committed
+    pub const PENDING_BUNDLE_COMMIT: Self = Self(1 << 5);
+    /// File is not fail-safe
+    pub const NOT_FAILSAFE: Self = Self(1 << 6);
+    /// File contents are not valid
+    pub const NOT_VALID: Self = Self(1 << 7);
+    /// File belongs to the system
+    pub const SYSTEM: Self = Self(1 << 8);
+    /// File is secure
+    pub const SECURE: Self = Self(1 << 9);
+    /// Secure file without signature
+    pub const NO_SIGNATURE: Self = Self(1 << 10);
+    /// Secure file can be written without a token
+    pub const PUBLIC_WRITE: Self = Self(1 << 11);
+    /// Secure file can be read without a token
+    pub const PUBLIC_READ: Self = Self(1 << 12);
+
+    const NAMES: [(Self, &'static str); 13] = [
+        (Self::OPEN_WRITE, "OPEN_WRITE"),
+        (Self::OPEN_ERROR, "OPEN_ERROR"),
+        (Self::MUST_COMMIT, "MUST_COMMIT"),
+        (Self::BUNDLE_FILE, "BUNDLE_FILE"),
+        (Self::PENDING_COMMIT, "PENDING_COMMIT"),
+        (Self::PENDING_BUNDLE_COMMIT, "PENDING_BUNDLE_COMMIT"),
+        (Self::NOT_FAILSAFE, "NOT_FAILSAFE"),
+        (Self::NOT_VALID, "NOT_VALID"),
+        (Self::SYSTEM, "SYS_FILE"),
+        (Self::SECURE, "SECURE"),
+        (Self::NO_SIGNATURE, "NOSIGNATURE"),
+        (Self::PUBLIC_WRITE, "PUBLIC_WRITE"),
+        (Self::PUBLIC_READ, "PUBLIC_READ"),
+    ];
+
+    /// Returns true if all properties in `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Parses properties separated by `|`, ignoring unknown names.
+    pub(crate) fn parse(s: &str) -> Self {
+        let mut flags = Self::default();
+        for name in s.split('|').map(str::trim) {
+            for (flag, flag_name) in Self::NAMES {
+                if name.eq_ignore_ascii_case(flag_name) {
+                    flags = flags | flag;
+                }
+            }
+        }
+        flags
+    }
+}
+
+impl BitOr for FileFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[derive(Debug, Clone, AtatResp, PartialEq)]
+pub struct OpenResponse {
+    /// File descriptor
+    #[at_arg(position = 0)]
+    pub id: u32,
+    /// Secure token, zero for non-secure files
+    #[at_arg(position = 1)]
+    pub token: u32,
+}
+
+/// Open a file
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+fileOpen",
+    OpenResponse,
+    timeout_ms = 1000,
+    quote_escape_strings = false
+)]
+pub struct Open<'a> {
+    /// File name
+    #[at_arg(position = 0, len = 362)]
+    pub name: Escaped<'a>,
+    /// Open options separated by `|`
+    #[at_arg(position = 1)]
+    pub options: String<128>,
+    /// Maximum file size when creating a file
+    #[at_arg(position = 2)]
+    pub size: u32,
+}
+
+/// Maximum length of a file signature.
+pub const MAX_SIGNATURE_LENGTH: usize = 256;
+
+/// Signature that closes a file without committing the written data.
+///
+/// A fail-safe file keeps its previous contents.
+pub const ABORT_SIGNATURE: &str = "A";
+
+/// Close a file
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+fileClose",
+    EmptyResponse,
+    timeout_ms = 1000,
+    quote_escape_strings = false
+)]
+pub struct Close<'a> {
+    /// File descriptor
+    #[at_arg(position = 0)]
+    pub id: u32,
+    /// Certificate used to verify the signature
+    #[at_arg(position = 1, len = 362)]
+    pub certificate: Escaped<'a>,
+    /// Signature of the file contents
+    #[at_arg(position = 2)]
+    pub signature: String<MAX_SIGNATURE_LENGTH>,
+}
+
+#[derive(Debug, Clone, AtatResp, PartialEq)]
+pub struct WriteResponse {
+    /// Number of bytes written
+    #[at_arg(position = 0)]
+    pub length: u16,
+}
+
+/// Write to a file
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+fileWrite",
+    WriteResponse,
+    timeout_ms = 1000,
+    quote_escape_strings = false
+)]
+pub struct Write<'a> {
+    /// File descriptor
+    #[at_arg(position = 0)]
+    pub id: u32,
+    /// Offset into the file
+    #[at_arg(position = 1)]
+    pub offset: u32,
+    /// Data format
+    #[at_arg(position = 2)]
+    pub format: DataFormat,
+    /// Length of the data
+    #[at_arg(position = 3)]
+    pub length: u16,
+    /// Data to write
+    #[at_arg(position = 4, len = 1460)]
+    pub data: Payload<'a>,
+}
+
+/// Data read from a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadResponse {
+    pub data: Vec<u8, { MAX_PAYLOAD_SIZE as usize }>,
+}
+
+impl atat::AtatResp for ReadResponse {}
+
+/// Read from a file
+///
+/// Response: `+fileread:<format>,<length>,<data>`
+pub struct Read {
+    /// File descriptor
+    pub id: u32,
+    /// Offset into the file
+    pub offset: u32,
+    /// Data format
+    pub format: DataFormat,
+    /// Maximum number of bytes to read
+    pub length: u16,
+}
+
+impl AtatCmd<64> for Read {
+    type Response = ReadResponse;
+
+    const MAX_TIMEOUT_MS: u32 = 1000;
+
+    fn as_bytes(&self) -> Vec<u8, 64> {
+        let mut cmd = String::<64>::new();
+        write!(
+            cmd,
+            "AT+fileRead={},{},{},{}\r\n",
+            self.id, self.offset, self.format as u8, self.length
+        )
+        .ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+        let _format: u8 = args.next()?;
+        let length: usize = args.next()?;
+
+        Ok(ReadResponse {
+            data: Vec::from_slice(args.data(length)?)
+                .map_err(|_| Error::Parse)?,
+        })
+    }
+}
+
+/// Delete a file
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+fileDel",
+    EmptyResponse,
+    timeout_ms = 1000,
+    quote_escape_strings = false
+)]
+pub struct Delete<'a> {
+    /// File name
+    #[at_arg(position = 0, len = 362)]
+    pub name: Escaped<'a>,
+    /// Secure token, zero for non-secure files
+    #[at_arg(position = 1)]
+    pub token: u32,
+}
+
+/// Information about a stored file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    /// File properties
+    pub flags: FileFlags,
+    /// Size of the contents in bytes
+    pub size: u32,
+    /// Allocated size in bytes
+    pub allocated_size: u32,
+    /// Secure tokens: master, read/write, write only and read only
+    pub tokens: [u32; 4],
+    /// Storage used in bytes
+    pub storage_size: u32,
+    /// Number of times the file was written
+    pub write_counter: u32,
+}
+
+impl atat::AtatResp for FileInfo {}
+
+/// Get information about a file
+///
+/// Response: `+filegetinfo:<flags>,<size>,<allocated size>,<token 0>,
+/// <token 1>,<token 2>,<token 3>,<storage size>,<write counter>`
+pub struct GetInfo<'a> {
+    /// File name
+    pub name: Escaped<'a>,
+    /// Secure token, zero for non-secure files
+    pub token: u32,
+}
+
+impl AtatCmd<400> for GetInfo<'_> {
+    type Response = FileInfo;
+
+    const MAX_TIMEOUT_MS: u32 = 1000;
+
+    fn as_bytes(&self) -> Vec<u8, 400> {
+        let mut cmd = String::<400>::new();
+        write!(cmd, "AT+fileGetInfo={},{}\r\n", self.name, self.token).ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+
+        Ok(FileInfo {
+            flags: FileFlags::parse(args.next_str()?),
+            size: args.next()?,
+            allocated_size: args.next()?,
+            tokens: [args.next()?, args.next()?, args.next()?, args.next()?],
+            storage_size: args.next()?,
+            write_counter: args.next()?,
+        })
+    }
+}
+
+/// Entry of the file list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    /// File name
+    pub name: String<{ MAX_FILE_NAME_LENGTH as usize }>,
+    /// Maximum size of the file in bytes
+    pub max_size: u32,
+    /// File properties
+    pub flags: FileFlags,
+    /// Number of allocated storage blocks
+    pub allocated_blocks: u32,
+}
+
+/// List of stored files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileList<const N: usize> {
+    pub entries: Vec<FileEntry, N>,
+}
+
+impl<const N: usize> atat::AtatResp for FileList<N> {}
+
+/// List all stored files
+///
+/// Response, one line per file:
+/// `+filegetfilelist:<name>,<max size>,<properties>,<allocated blocks>`
+///
+/// Parsing fails if there are more than `N` files.
+pub struct GetFileList<const N: usize>;
+
+impl<const N: usize> AtatCmd<22> for GetFileList<N> {
+    type Response = FileList<N>;
+
+    const MAX_TIMEOUT_MS: u32 = 5000;
+
+    fn as_bytes(&self) -> Vec<u8, 22> {
+        Vec::from_slice(b"AT+fileGetFileList\r\n").unwrap()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut entries = Vec::new();
+
+        for line in resp?.split(|&b| b == b'\n') {
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            let mut args = Args::new(line);
+            let entry = FileEntry {
+                name: args.next()?,
+                max_size: args.next()?,
+                flags: FileFlags::parse(args.next_str()?),
+                allocated_blocks: args.next()?,
+            };
+            entries.push(entry).map_err(|_| Error::Parse)?;
+        }
+
+        Ok(FileList { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_list() {
+        let resp = b"+filegetfilelist:/a.txt,1024,SECURE|NOSIGNATURE,2\r\n\
+            +filegetfilelist:/b,64,,1";
+        let list = GetFileList::<4>.parse(Ok(resp)).unwrap();
+
+        assert_eq!(list.entries.len(), 2);
+        assert_eq!(list.entries[0].name, "/a.txt");
+        assert_eq!(list.entries[0].max_size, 1024);
+        assert_eq!(list.entries[1].allocated_blocks, 1);
+    }
+
+    #[test]
+    fn file_list_rejects_long_names() {
+        let mut resp = std::vec::Vec::from(&b"+filegetfilelist:"[..]);
+        resp.resize(resp.len() + MAX_FILE_NAME_LENGTH as usize + 1, b'a');
+        resp.extend_from_slice(b",64,,1");
+
+        assert_eq!(GetFileList::<4>.parse(Ok(&resp)), Err(Error::Parse));
+    }
+}
@@ -0,0 +1,404 @@
+use super::{args::Args, DataFormat, EmptyResponse, Escaped, Payload};
+use crate::MAX_PAYLOAD_SIZE;
+use atat::atat_derive::{AtatCmd, AtatEnum, AtatResp};
+use atat::{AtatCmd, Error, InternalError};
+use core::fmt::Write;
+use heapless::{String, Vec};
+
+/// Maximum length of a request URI.
+pub const MAX_URI_LENGTH: usize = 256;
+
+/// Maximum length of a header value.
+pub const MAX_HEADER_LENGTH: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
+pub enum Method {
+    Get,
+    Post,
+    Head,
+    Options,
+    Put,
+    Delete,
+    Connect,
+}
+
+impl From<Method> for String<7> {
+    fn from(value: Method) -> Self {
+        String::from(match value {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Head => "HEAD",
+            Method::Options => "OPTIONS",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+            Method::Connect => "CONNECT",
+        })
+    }
+}
+
+/// Header fields that can be set on a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
+pub enum RequestHeader {
+    Accept,
+    AcceptCharset,
+    AcceptEncoding,
+    AcceptLanguage,
+    Allow,
+    Authorization,
+    CacheControl,
+    Connection,
+    ContentEncoding,
+    ContentLanguage,
+    ContentLocation,
+    ContentType,
+    Cookie,
+    Date,
+    Expect,
+    Forwarded,
+    From,
+    Host,
+    IfMatch,
+    IfModifiedSince,
+    IfNoneMatch,
+    IfRange,
+    IfUnmodifiedSince,
+    Origin,
+    ProxyAuthorization,
+    Range,
+    Te,
+    TransferEncoding,
+    Upgrade,
+    UserAgent,
+    Via,
+    Warning,
+}
+
+impl From<RequestHeader> for String<32> {
+    fn from(value: RequestHeader) -> Self {
+        String::from(match value {
+            RequestHeader::Accept => "REQ_ACCEPT",
+            RequestHeader::AcceptCharset => "REQ_ACCEPT_CHARSET",
+            RequestHeader::AcceptEncoding => "REQ_ACCEPT_ENCODING",
+            RequestHeader::AcceptLanguage => "REQ_ACCEPT_LANGUAGE",
+            RequestHeader::Allow => "REQ_ALLOW",
+            RequestHeader::Authorization => "REQ_AUTHORIZATION",
+            RequestHeader::CacheControl => "REQ_CACHE_CONTROL",
+            RequestHeader::Connection => "REQ_CONNECTION",
+            RequestHeader::ContentEncoding => "REQ_CONTENT_ENCODING",
+            RequestHeader::ContentLanguage => "REQ_CONTENT_LANGUAGE",
+            RequestHeader::ContentLocation => "REQ_CONTENT_LOCATION",
+            RequestHeader::ContentType => "REQ_CONTENT_TYPE",
+            RequestHeader::Cookie => "REQ_COOKIE",
+            RequestHeader::Date => "REQ_DATE",
+            RequestHeader::Expect => "REQ_EXPECT",
+            RequestHeader::Forwarded => "REQ_FORWARDED",
+            RequestHeader::From => "REQ_FROM",
+            RequestHeader::Host => "REQ_HOST",
+            RequestHeader::IfMatch => "REQ_IF_MATCH",
+            RequestHeader::IfModifiedSince => "REQ_IF_MODIFIED_SINCE",
+            RequestHeader::IfNoneMatch => "REQ_IF_NONE_MATCH",
+            RequestHeader::IfRange => "REQ_IF_RANGE",
+            RequestHeader::IfUnmodifiedSince => "REQ_IF_UNMODIFIED_SINCE",
+            RequestHeader::Origin => "REQ_ORIGIN",
+            RequestHeader::ProxyAuthorization => "REQ_PROXY_AUTHORIZATION",
+            RequestHeader::Range => "REQ_RANGE",
+            RequestHeader::Te => "REQ_TE",
+            RequestHeader::TransferEncoding => "REQ_TRANSFER_ENCODING",
+            RequestHeader::Upgrade => "REQ_UPGRADE",
+            RequestHeader::UserAgent => "REQ_USER_AGENT",
+            RequestHeader::Via => "REQ_VIA",
+            RequestHeader::Warning => "REQ_WARNING",
+        })
+    }
+}
+
+/// Header fields that can be read from a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
+pub enum ResponseHeader {
+    Age,
+    Allow,
+    CacheControl,
+    Connection,
+    ContentEncoding,
+    ContentLanguage,
+    ContentLength,
+    ContentLocation,
+    ContentRange,
+    ContentType,
+    Date,
+    ETag,
+    Expires,
+    LastModified,
+    Location,
+    ProxyAuthenticate,
+    RetryAfter,
+    Server,
+    SetCookie,
+    Trailer,
+    TransferEncoding,
+    Upgrade,
+    Vary,
+    Via,
+    WwwAuthenticate,
+    Warning,
+}
+
+impl From<ResponseHeader> for String<32> {
+    fn from(value: ResponseHeader) -> Self {
+        String::from(match value {
+            ResponseHeader::Age => "RES_AGE",
+            ResponseHeader::Allow => "RES_ALLOW",
+            ResponseHeader::CacheControl => "RES_CACHE_CONTROL",
+            ResponseHeader::Connection => "RES_CONNECTION",
+            ResponseHeader::ContentEncoding => "RES_CONTENT_ENCODING",
+            ResponseHeader::ContentLanguage => "RES_CONTENT_LANGUAGE",
+            ResponseHeader::ContentLength => "RES_CONTENT_LENGTH",
+            ResponseHeader::ContentLocation => "RES_CONTENT_LOCATION",
+            ResponseHeader::ContentRange => "RES_CONTENT_RANGE",
+            ResponseHeader::ContentType => "RES_CONTENT_TYPE",
+            ResponseHeader::Date => "RES_DATE",
+            ResponseHeader::ETag => "RES_ETAG",
+            ResponseHeader::Expires => "RES_EXPIRES",
+            ResponseHeader::LastModified => "RES_LAST_MODIFIED",
+            ResponseHeader::Location => "RES_LOCATION",
+            ResponseHeader::ProxyAuthenticate => "RES_PROXY_AUTHENTICATE",
+            ResponseHeader::RetryAfter => "RES_RETRY_AFTER",
+            ResponseHeader::Server => "RES_SERVER",
+            ResponseHeader::SetCookie => "RES_SET_COOKIE",
+            ResponseHeader::Trailer => "RES_TRAILER",
+            ResponseHeader::TransferEncoding => "RES_TRANSFER_ENCODING",
+            ResponseHeader::Upgrade => "RES_UPGRADE",
+            ResponseHeader::Vary => "RES_VARY",
+            ResponseHeader::Via => "RES_VIA",
+            ResponseHeader::WwwAuthenticate => "RES_WWW_AUTHENTICATE",
+            ResponseHeader::Warning => "RES_WARNING",
+        })
+    }
+}
+
+#[derive(Debug, Clone, AtatResp, PartialEq)]
+pub struct CreateResponse {
+    /// Client index
+    #[at_arg(position = 0)]
+    pub index: u8,
+}
+
+/// Create a HTTP client instance
+#[derive(AtatCmd)]
+#[at_cmd("+httpCreate", CreateResponse, timeout_ms = 100)]
+pub struct Create {}
+
+/// Destroy a HTTP client instance
+#[derive(AtatCmd)]
+#[at_cmd("+httpDestroy", EmptyResponse, timeout_ms = 100)]
+pub struct Destroy {
+    #[at_arg(position = 0)]
+    pub index: u8,
+}
+
+/// Connect a HTTP client to a server
+///
+/// The file names are left empty for plain HTTP connections.
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+httpConnect",
+    EmptyResponse,
+    timeout_ms = 10000,
+    quote_escape_strings = false
+)]
+pub struct Connect<'a> {
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Host name including the scheme and optional port
+    #[at_arg(position = 1, len = 258)]
+    pub host: Escaped<'a>,
+    /// Connection flags
+    #[at_arg(position = 2)]
+    pub flags: String<32>,
+    /// Client private key file name
+    #[at_arg(position = 3, len = 362)]
+    pub private_key: Escaped<'a>,
+    /// Client certificate file name
+    #[at_arg(position = 4, len = 362)]
+    pub certificate: Escaped<'a>,
+    /// Root CA certificate file name
+    #[at_arg(position = 5, len = 362)]
+    pub ca: Escaped<'a>,
+}
+
+/// Disconnect a HTTP client from the server
+#[derive(AtatCmd)]
+#[at_cmd("+httpDisconnect", EmptyResponse, timeout_ms = 1000)]
+pub struct Disconnect {
+    #[at_arg(position = 0)]
+    pub index: u8,
+}
+
+/// Set a request header
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+httpSetHeader",
+    EmptyResponse,
+    timeout_ms = 100,
+    quote_escape_strings = false
+)]
+pub struct SetHeader<'a> {
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Header field
+    #[at_arg(position = 1)]
+    pub option: String<32>,
+    /// Keep the header for all following requests
+    #[at_arg(position = 2)]
+    pub flags: String<16>,
+    /// Data format
+    #[at_arg(position = 3)]
+    pub format: DataFormat,
+    /// Length of the header value
+    #[at_arg(position = 4)]
+    pub length: u16,
+    /// Header value
+    #[at_arg(position = 5, len = 256)]
+    pub data: Payload<'a>,
+}
+
+#[derive(Debug, Clone, AtatResp, PartialEq)]
+pub struct SendRequestResponse {
+    /// HTTP status code
+    #[at_arg(position = 0)]
+    pub status: u16,
+}
+
+/// Send a request
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+httpSendReq",
+    SendRequestResponse,
+    timeout_ms = 10000,
+    quote_escape_strings = false
+)]
+pub struct SendRequest<'a> {
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Request method
+    #[at_arg(position = 1)]
+    pub method: String<7>,
+    /// Request URI
+    #[at_arg(position = 2, len = 514)]
+    pub uri: Escaped<'a>,
+    /// Request flags
+    #[at_arg(position = 3)]
+    pub flags: String<32>,
+    /// Data format of the body
+    #[at_arg(position = 4)]
+    pub format: DataFormat,
+    /// Length of the body
+    #[at_arg(position = 5)]
+    pub length: u16,
+    /// Request body
+    #[at_arg(position = 6, len = 1460)]
+    pub data: Payload<'a>,
+}
+
+/// Header value of a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub value: Vec<u8, MAX_HEADER_LENGTH>,
+}
+
+impl atat::AtatResp for Header {}
+
+/// Read a response header
+///
+/// Response: `+httpgetheader:<index>,<length>,<value>`
+pub struct GetHeader {
+    pub index: u8,
+    /// Header field
+    pub option: String<32>,
+}
+
+impl AtatCmd<64> for GetHeader {
+    type Response = Header;
+
+    const MAX_TIMEOUT_MS: u32 = 100;
+
+    fn as_bytes(&self) -> Vec<u8, 64> {
+        let mut cmd = String::<64>::new();
+        write!(
+            cmd,
+            "AT+httpGetHeader={},{},{}\r\n",
+            self.index, self.option, MAX_HEADER_LENGTH
+        )
+        .ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+        let _index: u8 = args.next()?;
+        let length: usize = args.next()?;
+
+        Ok(Header {
+            value: Vec::from_slice(args.data(length)?)
+                .map_err(|_| Error::Parse)?,
+        })
+    }
+}
+
+/// A chunk of a response body
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    /// More data is available to read
+    pub more: bool,
+    pub data: Vec<u8, { MAX_PAYLOAD_SIZE as usize }>,
+}
+
+impl atat::AtatResp for Body {}
+
+/// Read a chunk of the response body
+///
+/// Response: `+httpreadresbody:<index>,<more>,<format>,<length>,<data>`
+pub struct ReadBody {
+    pub index: u8,
+    /// Data format
+    pub format: DataFormat,
+    /// Maximum number of bytes to read
+    pub length: u16,
+}
+
+impl AtatCmd<48> for ReadBody {
+    type Response = Body;
+
+    const MAX_TIMEOUT_MS: u32 = 5000;
+
+    fn as_bytes(&self) -> Vec<u8, 48> {
+        let mut cmd = String::<48>::new();
+        write!(
+            cmd,
+            "AT+httpReadResBody={},{},{}\r\n",
+            self.index, self.format as u8, self.length
+        )
+        .ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+        let _index: u8 = args.next()?;
+        let more: u8 = args.next()?;
+        let _format: u8 = args.next()?;
+        let length: usize = args.next()?;
+
+        Ok(Body {
+            more: more != 0,
+            data: Vec::from_slice(args.data(length)?)
+                .map_err(|_| Error::Parse)?,
+        })
+    }
+}
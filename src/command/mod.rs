@@ -2,40 +2,497 @@
 //!
 //! See section 8 in the user manual.
 
+mod args;
 pub mod device;
+pub mod file;
+pub mod http;
+pub mod mqtt;
+pub mod netapp;
+pub mod ota;
 pub mod socket;
 pub mod wlan;
 
-use atat::atat_derive::{AtatResp, AtatUrc};
+use crate::MAX_PAYLOAD_SIZE;
+use atat::atat_derive::{AtatEnum, AtatResp};
+use atat::digest::{parser::urc_helper, ParseError};
+use atat::heapless_bytes::Bytes;
+use atat::serde_at::serde::{ser::Error as _, Serialize, Serializer};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use core::fmt::Write;
 
 #[derive(Debug, Clone, AtatResp, PartialEq)]
 pub struct EmptyResponse;
 
+/// Encoding of a data payload on the wire.
+///
+/// The driver encodes sent and decodes received payloads, so callers always
+/// deal in raw bytes. Plain text is sent as binary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, AtatEnum)]
+#[at_enum(u8)]
+pub enum DataFormat {
+    /// Raw bytes
+    #[default]
+    #[at_arg(value = 0)]
+    Binary = 0,
+    /// Base64 encoded, for links that are not 8-bit clean
+    #[at_arg(value = 1)]
+    Base64 = 1,
+}
+
+impl DataFormat {
+    /// Number of raw bytes that fit into `encoded` bytes on the wire.
+    pub const fn max_decoded(self, encoded: usize) -> usize {
+        match self {
+            Self::Binary => encoded,
+            Self::Base64 => encoded / 4 * 3,
+        }
+    }
+
+    /// Number of raw bytes in the received payload `data`.
+    pub(crate) fn decoded_len(self, data: &[u8]) -> usize {
+        match self {
+            Self::Binary => data.len(),
+            Self::Base64 => {
+                let padding =
+                    data.iter().rev().take(2).filter(|&&b| b == b'=').count();
+                (data.len() / 4 * 3).saturating_sub(padding)
+            }
+        }
+    }
+
+    /// Parses a format argument of a response or event.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        if s == "0" || s.eq_ignore_ascii_case("binary") {
+            Some(Self::Binary)
+        } else if s == "1" || s.eq_ignore_ascii_case("base64") {
+            Some(Self::Base64)
+        } else {
+            None
+        }
+    }
+
+    /// Encode a payload to be sent.
+    ///
+    /// Binary payloads are sent straight from `data`, base64 payloads are
+    /// encoded into `buf`.
+    pub(crate) fn encode<'a>(
+        self,
+        data: &'a [u8],
+        buf: &'a mut Bytes<{ MAX_PAYLOAD_SIZE as usize }>,
+    ) -> Result<Payload<'a>, crate::Error> {
+        match self {
+            Self::Binary if data.len() <= buf.capacity() => Ok(Payload(data)),
+            Self::Binary => Err(crate::Error::PayloadTooLarge),
+            Self::Base64 => {
+                buf.resize_default(buf.capacity())
+                    .map_err(|_| crate::Error::PayloadTooLarge)?;
+                let len = BASE64
+                    .encode_slice(data, buf.as_mut_slice())
+                    .map_err(|_| crate::Error::PayloadTooLarge)?;
+                buf.truncate(len);
+                Ok(Payload(buf))
+            }
+        }
+    }
+
+    /// Decode a received payload into `buf`, returning its length.
+    ///
+    /// Binary payloads longer than `buf` are truncated.
+    pub(crate) fn decode(
+        self,
+        data: &[u8],
+        buf: &mut [u8],
+    ) -> Result<usize, crate::Error> {
+        match self {
+            Self::Binary => {
+                let len = data.len().min(buf.len());
+                buf[..len].copy_from_slice(&data[..len]);
+                Ok(len)
+            }
+            Self::Base64 => BASE64
+                .decode_slice(data, buf)
+                .map_err(|_| crate::Error::At(atat::Error::Parse)),
+        }
+    }
+}
+
+/// Data payload of a command, serialised as it is.
+///
+/// Payloads borrow the caller's buffer, so only base64 needs a copy to hold
+/// the encoded data. Commands reserve [`MAX_PAYLOAD_SIZE`] bytes for a
+/// payload unless the module has a lower limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Payload<'a>(pub &'a [u8]);
+
+impl<'a> Payload<'a> {
+    /// The bytes sent on the wire.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Number of bytes sent on the wire.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if there is nothing to send.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Serialize for Payload<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Check an argument against the module's length limit.
+///
+/// Borrowed arguments are serialised into a buffer sized for the limit, so
+/// longer arguments have to be rejected before sending.
+pub(crate) fn check_length(
+    value: &str,
+    max: usize,
+) -> Result<(), crate::Error> {
+    if value.len() > max {
+        return Err(crate::Error::ArgumentTooLong {
+            length: value.len(),
+            max,
+        });
+    }
+
+    Ok(())
+}
+
+/// Longest argument that can be escaped, e.g. a HTTP URI.
+const MAX_ESCAPED_ARGUMENT_LENGTH: usize = http::MAX_URI_LENGTH;
+
+/// String argument escaped for the AT command line.
+///
+/// Arguments containing the separator, double quotes, backslashes or
+/// leading or trailing spaces are enclosed in double quotes, with double
+/// quotes and backslashes inside escaped by a backslash. Other arguments are
+/// sent as they are. Escaping doubles the length of an argument at most, so
+/// commands reserve `2 * max + 2` bytes for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Escaped<'a>(&'a str);
+
+impl<'a> Escaped<'a> {
+    /// Wrap an argument of at most `max` bytes.
+    pub fn new(value: &'a str, max: usize) -> Result<Self, crate::Error> {
+        check_length(value, max.min(MAX_ESCAPED_ARGUMENT_LENGTH))?;
+
+        Ok(Self(value))
+    }
+
+    /// The unescaped argument.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    fn needs_quotes(&self) -> bool {
+        self.0.starts_with(' ')
+            || self.0.ends_with(' ')
+            || self.0.contains([',', '"', '\\'])
+    }
+}
+
+impl core::fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if !self.needs_quotes() {
+            return f.write_str(self.0);
+        }
+
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            if c == '"' || c == '\\' {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        f.write_char('"')
+    }
+}
+
+impl Serialize for Escaped<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if !self.needs_quotes() {
+            return serializer.serialize_bytes(self.0.as_bytes());
+        }
+
+        let mut escaped =
+            heapless::String::<{ 2 * MAX_ESCAPED_ARGUMENT_LENGTH + 2 }>::new();
+        write!(escaped, "{}", self).map_err(S::Error::custom)?;
+        serializer.serialize_bytes(escaped.as_bytes())
+    }
+}
+
+/// Matches error responses carrying a reason, `ERROR:<reason>`.
+///
+/// Pass to the digester with `with_custom_error` so the reason, usually an
+/// error code, is reported as [`atat::Error::CustomMessage`] and converted
+/// into a typed [`crate::Error`].
+pub fn error_response(buf: &[u8]) -> Result<(&[u8], usize), ParseError> {
+    const TAG: &[u8] = b"ERROR:";
+
+    let start = buf
+        .iter()
+        .position(|&b| b != b'\r' && b != b'\n')
+        .unwrap_or(buf.len());
+    let line = &buf[start..];
+
+    let prefix = line.len().min(TAG.len());
+    if !line[..prefix].eq_ignore_ascii_case(&TAG[..prefix]) {
+        return Err(ParseError::NoMatch);
+    }
+    if prefix < TAG.len() {
+        return Err(ParseError::Incomplete);
+    }
+
+    match line.windows(2).position(|w| w == b"\r\n") {
+        Some(end) => Ok((&line[TAG.len()..end], start + end + 2)),
+        None => Err(ParseError::Incomplete),
+    }
+}
+
 /// Unsolicited result codes
-#[derive(Debug, PartialEq, Clone, AtatUrc)]
-pub enum Urc {
+///
+/// Received MQTT messages carry payloads of up to `N` bytes, use
+/// `Urc<{ mqtt::MAX_EVENT_MESSAGE_LENGTH }>` to receive any message the
+/// module reports. Every queued URC reserves room for such a payload, so
+/// the default of zero keeps the URC channel small when MQTT messages are
+/// not needed. Messages that do not fit are reported as
+/// [`mqtt::Event::Oversized`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum Urc<const N: usize = 0> {
     /// Startup message
-    #[at_urc("+eventstartup")]
     StartUp,
     /// General events
-    #[at_urc("+eventgeneral")]
     General,
     /// WLAN events
-    #[at_urc("+eventwlan")]
-    Wlan,
+    Wlan(wlan::Event),
     /// Socket events
-    #[at_urc("+eventsocket")]
-    Socket,
+    Socket(socket::Event),
     /// NetApp events
-    #[at_urc("+eventnetapp")]
-    NetApp,
+    NetApp(netapp::Event),
     /// MQTT events
-    #[at_urc("+eventmqtt")]
-    Mqtt,
+    Mqtt(mqtt::Event<N>),
     /// Fatal error events
-    #[at_urc("+eventfatalerror")]
     Fatal,
     /// Custom events
-    #[at_urc("+eventcustom")]
     Custom,
 }
+
+impl<const N: usize> atat::AtatUrc for Urc<N> {
+    type Response = Urc<N>;
+
+    fn parse(resp: &[u8]) -> Option<Self::Response> {
+        let index = resp.iter().position(|&b| b == b':').unwrap_or(resp.len());
+
+        Some(match &resp[..index] {
+            b"+eventstartup" => Urc::StartUp,
+            b"+eventgeneral" => Urc::General,
+            b"+eventwlan" => Urc::Wlan(wlan::Event::parse(resp)),
+            b"+eventsocket" => Urc::Socket(socket::Event::parse(resp)?),
+            b"+eventnetapp" => Urc::NetApp(netapp::Event::parse(resp)),
+            b"+eventmqtt" => Urc::Mqtt(mqtt::Event::parse(resp)?),
+            b"+eventfatalerror" => Urc::Fatal,
+            b"+eventcustom" => Urc::Custom,
+            _ => return None,
+        })
+    }
+}
+
+impl atat::Parser for Urc {
+    fn parse(buf: &[u8]) -> Result<(&[u8], usize), ParseError> {
+        let (_, urc) = atat::nom::branch::alt((
+            urc_helper(&b"+eventstartup"[..]),
+            urc_helper(&b"+eventgeneral"[..]),
+            urc_helper(&b"+eventwlan"[..]),
+            urc_helper(&b"+eventsocket"[..]),
+            urc_helper(&b"+eventnetapp"[..]),
+            urc_helper(&b"+eventmqtt"[..]),
+            urc_helper(&b"+eventfatalerror"[..]),
+            urc_helper(&b"+eventcustom"[..]),
+        ))(buf)?;
+
+        Ok(urc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atat::{AtatCmd, AtatUrc};
+    use std::string::ToString;
+
+    #[test]
+    fn data_format_sizes() {
+        assert_eq!(DataFormat::Binary.max_decoded(1460), 1460);
+        assert_eq!(DataFormat::Base64.max_decoded(1460), 1095);
+        assert_eq!(DataFormat::Base64.max_decoded(3), 0);
+    }
+
+    #[test]
+    fn data_format_names() {
+        assert_eq!(DataFormat::parse("0"), Some(DataFormat::Binary));
+        assert_eq!(DataFormat::parse("BASE64"), Some(DataFormat::Base64));
+        assert_eq!(DataFormat::parse("2"), None);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let mut buf = Bytes::new();
+        let encoded = DataFormat::Binary.encode(b"a\r\nOK", &mut buf).unwrap();
+        assert_eq!(encoded, Payload(b"a\r\nOK"));
+
+        let mut decoded = [0; 3];
+        assert_eq!(
+            DataFormat::Binary.decode(encoded.as_bytes(), &mut decoded),
+            Ok(3)
+        );
+        assert_eq!(&decoded, b"a\r\n");
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let mut buf = Bytes::new();
+        let encoded = DataFormat::Base64.encode(b"hello", &mut buf).unwrap();
+        assert_eq!(encoded.as_bytes(), b"aGVsbG8=");
+
+        let mut decoded = [0; 8];
+        assert_eq!(
+            DataFormat::Base64.decode(encoded.as_bytes(), &mut decoded),
+            Ok(5)
+        );
+        assert_eq!(&decoded[..5], b"hello");
+    }
+
+    #[test]
+    fn encode_too_large() {
+        let data = [0; MAX_PAYLOAD_SIZE as usize + 1];
+
+        let mut buf = Bytes::new();
+
+        assert_eq!(
+            DataFormat::Binary.encode(&data, &mut buf),
+            Err(crate::Error::PayloadTooLarge)
+        );
+        assert_eq!(
+            DataFormat::Base64.encode(&data[..1096], &mut buf),
+            Err(crate::Error::PayloadTooLarge)
+        );
+        assert!(DataFormat::Base64.encode(&data[..1095], &mut buf).is_ok());
+    }
+
+    #[test]
+    fn decode_invalid_base64() {
+        let mut buf = [0; 8];
+
+        assert_eq!(
+            DataFormat::Base64.decode(b"a?==", &mut buf),
+            Err(crate::Error::At(atat::Error::Parse))
+        );
+    }
+
+    #[test]
+    fn escaped_plain() {
+        let value = Escaped::new("pool.ntp.org", 32).unwrap();
+
+        assert_eq!(value.to_string(), "pool.ntp.org");
+        assert_eq!(value.as_str(), "pool.ntp.org");
+    }
+
+    #[test]
+    fn escaped_quoted() {
+        for (value, escaped) in [
+            ("a,b", r#""a,b""#),
+            (r#"say "hi""#, r#""say \"hi\"""#),
+            (r"back\slash", r#""back\\slash""#),
+            (" padded ", r#"" padded ""#),
+        ] {
+            assert_eq!(Escaped::new(value, 32).unwrap().to_string(), escaped);
+        }
+    }
+
+    #[test]
+    fn escaped_length() {
+        assert_eq!(
+            Escaped::new("abcd", 3),
+            Err(crate::Error::ArgumentTooLong { length: 4, max: 3 })
+        );
+    }
+
+    #[test]
+    fn escaped_argument() {
+        let cmd = netapp::SntpServer {
+            index: 0,
+            address: Escaped::new(r#"a,"b""#, 32).unwrap(),
+        };
+
+        assert_eq!(
+            &cmd.as_bytes()[..],
+            b"AT+netAppSet=SNTP_CLIENT,SERVER,0,\"a,\\\"b\\\"\"\r\n"
+        );
+    }
+
+    #[test]
+    fn error_reasons() {
+        assert_eq!(
+            error_response(b"\r\nERROR:connect,-468\r\n").ok(),
+            Some((&b"connect,-468"[..], 22))
+        );
+        assert!(matches!(
+            error_response(b"\r\nERR"),
+            Err(ParseError::Incomplete)
+        ));
+        assert!(matches!(
+            error_response(b"\r\nERROR:-1"),
+            Err(ParseError::Incomplete)
+        ));
+        assert!(matches!(
+            error_response(b"\r\nERROR\r\n"),
+            Err(ParseError::NoMatch)
+        ));
+        assert!(matches!(
+            error_response(b"\r\nOK\r\n"),
+            Err(ParseError::NoMatch)
+        ));
+    }
+
+    #[test]
+    fn urcs() {
+        assert_eq!(<Urc>::parse(b"+eventstartup:"), Some(Urc::StartUp));
+        assert_eq!(
+            <Urc>::parse(b"+eventwlan:provisioning"),
+            Some(Urc::Wlan(wlan::Event::Other))
+        );
+        assert_eq!(
+            <Urc>::parse(b"+eventmqtt:disconnect"),
+            Some(Urc::Mqtt(mqtt::Event::Disconnect))
+        );
+        assert_eq!(<Urc>::parse(b"+eventsocket:unknown,1"), None);
+        assert_eq!(<Urc>::parse(b"+unknown:1"), None);
+    }
+
+    #[test]
+    fn urc_lines() {
+        let buf = b"\r\n+eventnetapp:ipv4_acquired,1.2.3.4,1.2.3.1,1.2.3.1\r\n";
+
+        assert_eq!(
+            <Urc as atat::Parser>::parse(buf).ok(),
+            Some((
+                &b"+eventnetapp:ipv4_acquired,1.2.3.4,1.2.3.1,1.2.3.1"[..],
+                buf.len()
+            ))
+        );
+    }
+}
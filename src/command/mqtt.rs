@@ -0,0 +1,534 @@
+use super::{args::Args, DataFormat, EmptyResponse, Escaped, Payload};
+use atat::atat_derive::{AtatCmd, AtatEnum, AtatResp};
+use core::str::FromStr;
+use heapless::{String, Vec};
+
+/// Maximum length of a client identifier.
+pub const MAX_CLIENT_ID_LENGTH: usize = 64;
+
+/// Maximum length of a topic name or filter.
+pub const MAX_TOPIC_LENGTH: usize = 128;
+
+/// Maximum length of a message received through an event.
+pub const MAX_EVENT_MESSAGE_LENGTH: usize = 512;
+
+/// Maximum length of a last will message.
+pub const MAX_WILL_MESSAGE_LENGTH: usize = 256;
+
+/// Maximum number of topics acknowledged by a single SUBACK.
+pub const MAX_SUBACK_TOPICS: usize = 4;
+
+/// Maximum length of a user name or password.
+pub const MAX_CREDENTIAL_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
+pub enum ProtocolVersion {
+    V3_1,
+    V3_1_1,
+}
+
+impl From<ProtocolVersion> for String<6> {
+    fn from(value: ProtocolVersion) -> Self {
+        String::from(match value {
+            ProtocolVersion::V3_1 => "v3_1",
+            ProtocolVersion::V3_1_1 => "v3_1_1",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
+pub enum QoS {
+    AtMostOnce,
+    AtLeastOnce,
+    ExactlyOnce,
+}
+
+impl From<QoS> for String<4> {
+    fn from(value: QoS) -> Self {
+        String::from(match value {
+            QoS::AtMostOnce => "QOS0",
+            QoS::AtLeastOnce => "QOS1",
+            QoS::ExactlyOnce => "QOS2",
+        })
+    }
+}
+
+impl FromStr for QoS {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "QOS0" | "0" => Ok(QoS::AtMostOnce),
+            "QOS1" | "1" => Ok(QoS::AtLeastOnce),
+            "QOS2" | "2" => Ok(QoS::ExactlyOnce),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
+pub enum SetOption {
+    User,
+    Password,
+    Will,
+    KeepAlive,
+    Clean,
+}
+
+impl From<SetOption> for String<9> {
+    fn from(value: SetOption) -> Self {
+        String::from(match value {
+            SetOption::User => "USER",
+            SetOption::Password => "PASSWORD",
+            SetOption::Will => "WILL",
+            SetOption::KeepAlive => "KEEPALIVE",
+            SetOption::Clean => "CLEAN",
+        })
+    }
+}
+
+#[derive(Debug, Clone, AtatResp, PartialEq)]
+pub struct CreateResponse {
+    /// Client index
+    #[at_arg(position = 0)]
+    pub index: u8,
+}
+
+/// Create a MQTT client instance
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+mqttCreate",
+    CreateResponse,
+    timeout_ms = 1000,
+    quote_escape_strings = false
+)]
+pub struct Create<'a> {
+    /// Client identifier
+    #[at_arg(position = 0, len = 130)]
+    pub client_id: Escaped<'a>,
+    /// Connection flags separated by `|`
+    #[at_arg(position = 1)]
+    pub flags: String<96>,
+    /// Broker IP address or host name
+    #[at_arg(position = 2, len = 258)]
+    pub address: Escaped<'a>,
+    /// Broker port
+    #[at_arg(position = 3)]
+    pub port: u16,
+    /// Client private key file name
+    #[at_arg(position = 4, len = 362)]
+    pub private_key: Escaped<'a>,
+    /// Client certificate file name
+    #[at_arg(position = 5, len = 362)]
+    pub certificate: Escaped<'a>,
+    /// Root CA certificate file name
+    #[at_arg(position = 6, len = 362)]
+    pub ca: Escaped<'a>,
+    /// Protocol version
+    #[at_arg(position = 7)]
+    pub protocol: String<6>,
+    /// Wait for the transmission to finish before returning
+    #[at_arg(position = 8)]
+    pub blocking_send: u8,
+    /// Data format of received messages
+    #[at_arg(position = 9)]
+    pub format: DataFormat,
+}
+
+/// Delete a MQTT client instance
+#[derive(AtatCmd)]
+#[at_cmd("+mqttDelete", EmptyResponse, timeout_ms = 1000)]
+pub struct Delete {
+    #[at_arg(position = 0)]
+    pub index: u8,
+}
+
+/// Connect to the broker
+#[derive(AtatCmd)]
+#[at_cmd("+mqttConnect", EmptyResponse, timeout_ms = 10000)]
+pub struct Connect {
+    #[at_arg(position = 0)]
+    pub index: u8,
+}
+
+/// Disconnect from the broker
+#[derive(AtatCmd)]
+#[at_cmd("+mqttDisconnect", EmptyResponse, timeout_ms = 1000)]
+pub struct Disconnect {
+    #[at_arg(position = 0)]
+    pub index: u8,
+}
+
+/// Publish a message
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+mqttPublish",
+    EmptyResponse,
+    timeout_ms = 5000,
+    quote_escape_strings = false
+)]
+pub struct Publish<'a> {
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Topic name
+    #[at_arg(position = 1, len = 258)]
+    pub topic: Escaped<'a>,
+    /// Quality of service
+    #[at_arg(position = 2)]
+    pub qos: String<4>,
+    /// Retain the message on the broker
+    #[at_arg(position = 3)]
+    pub retain: u8,
+    /// Length of the message
+    #[at_arg(position = 4)]
+    pub length: u16,
+    /// Message
+    #[at_arg(position = 5, len = 1460)]
+    pub message: Payload<'a>,
+}
+
+/// Subscribe to a topic
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+mqttSubscribe",
+    EmptyResponse,
+    timeout_ms = 5000,
+    quote_escape_strings = false
+)]
+pub struct Subscribe<'a> {
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Number of topics
+    #[at_arg(position = 1)]
+    pub count: u8,
+    /// Topic filter
+    #[at_arg(position = 2, len = 258)]
+    pub topic: Escaped<'a>,
+    /// Maximum quality of service
+    #[at_arg(position = 3)]
+    pub qos: String<4>,
+}
+
+/// Unsubscribe from a topic
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+mqttUnsubscribe",
+    EmptyResponse,
+    timeout_ms = 5000,
+    quote_escape_strings = false
+)]
+pub struct Unsubscribe<'a> {
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Number of topics
+    #[at_arg(position = 1)]
+    pub count: u8,
+    /// Topic filter
+    #[at_arg(position = 2, len = 258)]
+    pub topic: Escaped<'a>,
+}
+
+/// Set a client option
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+mqttSet",
+    EmptyResponse,
+    timeout_ms = 100,
+    quote_escape_strings = false
+)]
+pub struct Set<'a> {
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Option
+    #[at_arg(position = 1)]
+    pub option: String<9>,
+    /// Option value
+    #[at_arg(position = 2, len = 130)]
+    pub value: Escaped<'a>,
+}
+
+/// Set the last will message
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+mqttSet",
+    EmptyResponse,
+    timeout_ms = 100,
+    quote_escape_strings = false
+)]
+pub struct SetWill<'a> {
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Option, always [`SetOption::Will`]
+    #[at_arg(position = 1)]
+    pub option: String<9>,
+    /// Topic name
+    #[at_arg(position = 2, len = 258)]
+    pub topic: Escaped<'a>,
+    /// Quality of service
+    #[at_arg(position = 3)]
+    pub qos: String<4>,
+    /// Retain the message on the broker
+    #[at_arg(position = 4)]
+    pub retain: u8,
+    /// Length of the message
+    #[at_arg(position = 5)]
+    pub length: u16,
+    /// Message
+    #[at_arg(position = 6, len = 256)]
+    pub message: Payload<'a>,
+}
+
+/// Message received on a subscribed topic
+///
+/// The payload holds up to `N` bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message<const N: usize> {
+    /// Topic name
+    pub topic: String<MAX_TOPIC_LENGTH>,
+    /// Quality of service
+    pub qos: QoS,
+    /// Message was retained by the broker
+    pub retain: bool,
+    /// Message is a duplicate of an earlier delivery
+    pub duplicate: bool,
+    /// Decoded payload
+    pub payload: Vec<u8, N>,
+}
+
+/// MQTT event
+///
+/// Event: `+eventmqtt:<event>,...`
+///
+/// Received messages carry payloads of up to `N` bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<const N: usize> {
+    /// Connection acknowledged by the broker
+    ConnAck {
+        /// Connect return code, zero on success
+        return_code: u8,
+    },
+    /// Publication acknowledged by the broker
+    PubAck {
+        /// Packet identifier
+        packet_id: u16,
+    },
+    /// Subscription acknowledged by the broker
+    SubAck {
+        /// Granted quality of service for each topic, `0x80` on failure
+        granted: Vec<u8, MAX_SUBACK_TOPICS>,
+    },
+    /// Unsubscription acknowledged by the broker
+    UnsubAck,
+    /// Message received
+    Recv(Message<N>),
+    /// Message received that does not fit into [`Message`] and was
+    /// discarded
+    Oversized {
+        /// Length of the topic
+        topic_length: usize,
+        /// Length of the payload on the wire
+        length: usize,
+    },
+    /// Disconnected from the broker
+    Disconnect,
+}
+
+impl<const N: usize> Event<N> {
+    pub(crate) fn parse(resp: &[u8]) -> Option<Self> {
+        let mut args = Args::new(resp);
+        let event = args.next_str().ok()?;
+
+        if event.eq_ignore_ascii_case("recv") {
+            return Self::parse_recv(args);
+        }
+
+        if event.eq_ignore_ascii_case("disconnect") {
+            return Some(Event::Disconnect);
+        }
+
+        // acknowledgements are reported as `operation,<type>,...`
+        let event = if event.eq_ignore_ascii_case("operation") {
+            args.next_str().ok()?
+        } else {
+            event
+        };
+
+        if event.eq_ignore_ascii_case("connack") {
+            Some(Event::ConnAck {
+                return_code: args.next().ok()?,
+            })
+        } else if event.eq_ignore_ascii_case("puback") {
+            Some(Event::PubAck {
+                packet_id: args.next().unwrap_or_default(),
+            })
+        } else if event.eq_ignore_ascii_case("suback") {
+            let mut granted = Vec::new();
+            while let Ok(qos) = args.next() {
+                granted.push(qos).ok()?;
+            }
+            Some(Event::SubAck { granted })
+        } else if event.eq_ignore_ascii_case("unsuback") {
+            Some(Event::UnsubAck)
+        } else {
+            None
+        }
+    }
+
+    /// `recv,<topic>,<qos>,<retain>,<duplicate>,<format>,<length>,<payload>`
+    ///
+    /// Topics containing separators are enclosed in double quotes.
+    fn parse_recv(mut args: Args) -> Option<Self> {
+        let topic = args.next_quoted().ok()?;
+        let qos = args.next_str().ok()?.parse().ok()?;
+        let retain: u8 = args.next().ok()?;
+        let duplicate: u8 = args.next().ok()?;
+        let format = DataFormat::parse(args.next_str().ok()?)?;
+        let length: usize = args.next().ok()?;
+        let data = args.data(length).ok()?;
+
+        let topic_length = unescape(topic).count();
+        let oversized = Event::Oversized {
+            topic_length,
+            length,
+        };
+        if topic_length > MAX_TOPIC_LENGTH {
+            return Some(oversized);
+        }
+        let topic: Vec<u8, MAX_TOPIC_LENGTH> = unescape(topic).collect();
+        let topic = core::str::from_utf8(&topic).ok()?.parse().ok()?;
+
+        if format.decoded_len(data) > N {
+            return Some(oversized);
+        }
+        let mut payload = Vec::new();
+        payload.resize_default(N).ok()?;
+        let length = format.decode(data, &mut payload).ok()?;
+        payload.truncate(length);
+
+        Some(Event::Recv(Message {
+            topic,
+            qos,
+            retain: retain != 0,
+            duplicate: duplicate != 0,
+            payload,
+        }))
+    }
+}
+
+/// Bytes of a quoted argument with backslash escapes removed.
+fn unescape(quoted: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    let mut escaped = false;
+
+    quoted.iter().copied().filter(move |&b| {
+        let keep = escaped || b != b'\\';
+        escaped = !escaped && b == b'\\';
+        keep
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Event = super::Event<16>;
+
+    #[test]
+    fn recv_event() {
+        let event = Event::parse(b"+eventmqtt:recv,a/b,QOS1,1,0,0,5,hi,\r\n");
+
+        assert_eq!(
+            event,
+            Some(Event::Recv(Message {
+                topic: "a/b".into(),
+                qos: QoS::AtLeastOnce,
+                retain: true,
+                duplicate: false,
+                payload: Vec::from_slice(b"hi,\r\n").unwrap(),
+            }))
+        );
+    }
+
+    #[test]
+    fn recv_base64_event() {
+        let event = Event::parse(b"+eventmqtt:recv,t,0,0,1,1,8,aGVsbG8=");
+
+        let Some(Event::Recv(message)) = event else {
+            panic!("not a message: {:?}", event);
+        };
+        assert!(message.duplicate);
+        assert_eq!(&message.payload[..], b"hello");
+    }
+
+    #[test]
+    fn recv_quoted_topic() {
+        let event =
+            Event::parse(br#"+eventmqtt:recv,"a,\"b\"\\c",0,0,0,0,1,x"#);
+
+        let Some(Event::Recv(message)) = event else {
+            panic!("not a message: {:?}", event);
+        };
+        assert_eq!(message.topic, r#"a,"b"\c"#);
+    }
+
+    #[test]
+    fn recv_long_topic() {
+        let mut event = std::vec::Vec::from(&b"+eventmqtt:recv,"[..]);
+        event.resize(event.len() + MAX_TOPIC_LENGTH + 1, b'a');
+        event.extend_from_slice(b",0,0,0,0,1,x");
+
+        assert_eq!(
+            Event::parse(&event),
+            Some(Event::Oversized {
+                topic_length: MAX_TOPIC_LENGTH + 1,
+                length: 1
+            })
+        );
+    }
+
+    #[test]
+    fn recv_long_payload() {
+        let payload = b"+eventmqtt:recv,t,0,0,0,0,17,aaaaaaaaaaaaaaaaa";
+        let base64 = b"+eventmqtt:recv,t,0,0,0,1,24,YWFhYWFhYWFhYWFhYWFhYWE=";
+
+        assert_eq!(
+            Event::parse(payload),
+            Some(Event::Oversized {
+                topic_length: 1,
+                length: 17
+            })
+        );
+        assert_eq!(
+            Event::parse(base64),
+            Some(Event::Oversized {
+                topic_length: 1,
+                length: 24
+            })
+        );
+        assert!(matches!(
+            Event::parse(
+                b"+eventmqtt:recv,t,0,0,0,1,24,YWFhYWFhYWFhYWFhYWFhYQ=="
+            ),
+            Some(Event::Recv(_))
+        ));
+    }
+
+    #[test]
+    fn acknowledgements() {
+        assert_eq!(
+            Event::parse(b"+eventmqtt:operation,CONNACK,0"),
+            Some(Event::ConnAck { return_code: 0 })
+        );
+        assert_eq!(
+            Event::parse(b"+eventmqtt:puback,7"),
+            Some(Event::PubAck { packet_id: 7 })
+        );
+        assert_eq!(
+            Event::parse(b"+eventmqtt:operation,SUBACK,1,128"),
+            Some(Event::SubAck {
+                granted: Vec::from_slice(&[1, 0x80]).unwrap()
+            })
+        );
+        assert_eq!(Event::parse(b"+eventmqtt:unsuback"), Some(Event::UnsubAck));
+        assert_eq!(Event::parse(b"+eventmqtt:unknown"), None);
+    }
+}
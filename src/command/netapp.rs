@@ -0,0 +1,143 @@
+use super::{args::Args, EmptyResponse, Escaped};
+use atat::atat_derive::AtatCmd;
+use core::net::Ipv4Addr;
+
+/// Enable or disable the SNTP client.
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+netAppSet=SNTP_CLIENT,ENABLE,",
+    EmptyResponse,
+    timeout_ms = 100,
+    value_sep = false
+)]
+pub struct SntpEnable {
+    #[at_arg(position = 0)]
+    pub enable: bool,
+}
+
+/// Set the interval between automatic SNTP updates.
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+netAppSet=SNTP_CLIENT,UPDATE_INTERVAL,",
+    EmptyResponse,
+    timeout_ms = 100,
+    value_sep = false
+)]
+pub struct SntpUpdateInterval {
+    /// Interval in seconds
+    #[at_arg(position = 0)]
+    pub interval_secs: u32,
+}
+
+/// Set the local time zone applied to SNTP time.
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+netAppSet=SNTP_CLIENT,TIME_ZONE,",
+    EmptyResponse,
+    timeout_ms = 100,
+    value_sep = false
+)]
+pub struct SntpTimeZone {
+    /// Offset from UTC in minutes
+    #[at_arg(position = 0)]
+    pub offset_mins: i16,
+}
+
+/// Set one of the SNTP servers.
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+netAppSet=SNTP_CLIENT,SERVER,",
+    EmptyResponse,
+    timeout_ms = 100,
+    value_sep = false,
+    quote_escape_strings = false
+)]
+pub struct SntpServer<'a> {
+    /// Server index
+    #[at_arg(position = 0)]
+    pub index: u8,
+    /// Server host name or IP address, at most
+    /// [`MAX_HOST_NAME_LENGTH`](crate::MAX_HOST_NAME_LENGTH) bytes
+    #[at_arg(position = 1, len = 258)]
+    pub address: Escaped<'a>,
+}
+
+/// Update the module clock from the configured SNTP servers.
+#[derive(AtatCmd)]
+#[at_cmd("+netAppUpdateTime", EmptyResponse, timeout_ms = 5000)]
+pub struct UpdateTime {}
+
+/// IPv4 configuration acquired by the module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv4Config {
+    /// Own address
+    pub address: Ipv4Addr,
+    /// Default gateway
+    pub gateway: Ipv4Addr,
+    /// DNS server
+    pub dns: Ipv4Addr,
+}
+
+/// NetApp event
+///
+/// Event: `+eventnetapp:<event>,...`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// IPv4 address acquired, e.g. by DHCP
+    ///
+    /// Event: `+eventnetapp:ipv4_acquired,<address>,<gateway>,<dns>`
+    Ipv4Acquired(Ipv4Config),
+    /// Any other event
+    Other,
+}
+
+impl Event {
+    pub(crate) fn parse(resp: &[u8]) -> Self {
+        Self::parse_known(resp).unwrap_or(Event::Other)
+    }
+
+    fn parse_known(resp: &[u8]) -> Option<Self> {
+        let mut args = Args::new(resp);
+        let event = args.next_str().ok()?;
+
+        if event.eq_ignore_ascii_case("ipv4_acquired") {
+            Some(Event::Ipv4Acquired(Ipv4Config {
+                address: args.next().ok()?,
+                gateway: args.next().ok()?,
+                dns: args.next().ok()?,
+            }))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv4_acquired_event() {
+        assert_eq!(
+            Event::parse(
+                b"+eventnetapp:IPV4_ACQUIRED,192.168.1.20,192.168.1.1,8.8.8.8"
+            ),
+            Event::Ipv4Acquired(Ipv4Config {
+                address: Ipv4Addr::new(192, 168, 1, 20),
+                gateway: Ipv4Addr::new(192, 168, 1, 1),
+                dns: Ipv4Addr::new(8, 8, 8, 8),
+            })
+        );
+    }
+
+    #[test]
+    fn other_events() {
+        for event in [
+            &b"+eventnetapp:ipv6_acquired,::1"[..],
+            b"+eventnetapp:ipv4_acquired,192.168.1.20",
+            b"+eventnetapp:ipv4_acquired,192.168.1.300,1.1.1.1,1.1.1.1",
+        ] {
+            assert_eq!(Event::parse(event), Event::Other);
+        }
+    }
+}
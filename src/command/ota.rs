@@ -0,0 +1,28 @@
+//! Firmware update commands.
+
+use super::{EmptyResponse, Escaped};
+use atat::atat_derive::AtatCmd;
+
+/// Install a firmware image from the file system on the next reboot
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+otaStart",
+    EmptyResponse,
+    timeout_ms = 1000,
+    quote_escape_strings = false
+)]
+pub struct Start<'a> {
+    /// File containing the update image
+    #[at_arg(position = 0, len = 362)]
+    pub file: Escaped<'a>,
+}
+
+/// Accept the running firmware image
+#[derive(AtatCmd)]
+#[at_cmd("+otaCommit", EmptyResponse, timeout_ms = 1000)]
+pub struct Commit {}
+
+/// Reject the running firmware image and restore the previous one
+#[derive(AtatCmd)]
+#[at_cmd("+otaRollback", EmptyResponse, timeout_ms = 1000)]
+pub struct Rollback {}
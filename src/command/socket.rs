@@ -1,6 +1,11 @@
-use super::EmptyResponse;
-use atat::atat_derive::{AtatCmd, AtatEnum};
-use heapless::String;
+use super::{args::Args, DataFormat, EmptyResponse, Payload};
+use crate::{MAX_IP_ADDRESS_LENGTH, MAX_PAYLOAD_SIZE};
+use atat::atat_derive::{AtatCmd, AtatEnum, AtatResp};
+use atat::{AtatCmd, Error, InternalError};
+use core::fmt::Write as _;
+use core::net::{IpAddr, SocketAddr};
+use core::ops::BitOr;
+use heapless::{String, Vec};
 
 #[derive(Debug, Clone, Copy, AtatEnum)]
 pub enum SocketFamily {
@@ -8,9 +13,9 @@ pub enum SocketFamily {
     Inet6,
 }
 
-impl Into<String<5>> for SocketFamily {
-    fn into(self) -> String<5> {
-        String::from(match self {
+impl From<SocketFamily> for String<5> {
+    fn from(value: SocketFamily) -> Self {
+        String::from(match value {
             SocketFamily::Inet => "INET",
             SocketFamily::Inet6 => "INET6",
         })
@@ -23,9 +28,9 @@ pub enum SocketType {
     Dgram,
 }
 
-impl Into<String<6>> for SocketType {
-    fn into(self) -> String<6> {
-        String::from(match self {
+impl From<SocketType> for String<6> {
+    fn from(value: SocketType) -> Self {
+        String::from(match value {
             SocketType::Stream => "STREAM",
             SocketType::Dgram => "DGRAM",
         })
@@ -39,9 +44,9 @@ pub enum SocketProtocol {
     Sec,
 }
 
-impl Into<String<3>> for SocketProtocol {
-    fn into(self) -> String<3> {
-        String::from(match self {
+impl From<SocketProtocol> for String<3> {
+    fn from(value: SocketProtocol) -> Self {
+        String::from(match value {
             SocketProtocol::Tcp => "TCP",
             SocketProtocol::Udp => "UDP",
             SocketProtocol::Sec => "SEC",
@@ -49,11 +54,18 @@ impl Into<String<3>> for SocketProtocol {
     }
 }
 
+#[derive(Debug, Clone, AtatResp, PartialEq)]
+pub struct SocketResponse {
+    /// Socket identifier
+    #[at_arg(position = 0)]
+    pub id: u8,
+}
+
 /// Create a socket
 #[derive(AtatCmd)]
 #[at_cmd(
     "+socket",
-    EmptyResponse,
+    SocketResponse,
     timeout_ms = 100,
     quote_escape_strings = false
 )]
@@ -76,7 +88,12 @@ pub struct Close {
 
 /// Bind a socket
 #[derive(AtatCmd)]
-#[at_cmd("+bind", EmptyResponse, timeout_ms = 100)]
+#[at_cmd(
+    "+bind",
+    EmptyResponse,
+    timeout_ms = 100,
+    quote_escape_strings = false
+)]
 pub struct Bind {
     #[at_arg(position = 0)]
     pub socket_id: u8,
@@ -85,5 +102,949 @@ pub struct Bind {
     #[at_arg(position = 2)]
     pub local_port: u16,
     #[at_arg(position = 3)]
-    pub local_address: String<15>,
+    pub local_address: String<{ MAX_IP_ADDRESS_LENGTH as usize }>,
+}
+
+/// Listen for connections on a bound socket
+#[derive(AtatCmd)]
+#[at_cmd("+listen", EmptyResponse, timeout_ms = 100)]
+pub struct Listen {
+    #[at_arg(position = 0)]
+    pub socket_id: u8,
+    /// Maximum number of pending connections
+    #[at_arg(position = 1)]
+    pub backlog: u8,
+}
+
+/// Accepted connection
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptResponse {
+    /// Socket identifier of the connection
+    pub socket_id: u8,
+    /// Address of the peer
+    pub remote: SocketAddr,
+}
+
+impl atat::AtatResp for AcceptResponse {}
+
+/// Accept a connection on a listening socket
+///
+/// Response: `+accept:<id>,<family>,<port>,<address>`
+///
+/// On a blocking socket the module only responds once a peer connects. For
+/// secure sockets this includes the TLS handshake.
+pub struct Accept {
+    pub socket_id: u8,
+    pub family: String<5>,
+}
+
+impl AtatCmd<24> for Accept {
+    type Response = AcceptResponse;
+
+    const MAX_TIMEOUT_MS: u32 = 30000;
+
+    fn as_bytes(&self) -> Vec<u8, 24> {
+        let mut cmd = String::<24>::new();
+        write!(cmd, "AT+accept={},{}\r\n", self.socket_id, self.family).ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+        let socket_id = args.next()?;
+        let _family = args.next_str()?;
+        let port = args.next()?;
+        let address: IpAddr = args.next()?;
+
+        Ok(AcceptResponse {
+            socket_id,
+            remote: SocketAddr::new(address, port),
+        })
+    }
+}
+
+/// Connect a socket to a remote address
+///
+/// Response: `+connect:<id>,<port>,<address>`
+///
+/// For secure sockets this includes the TLS handshake.
+pub struct Connect {
+    pub socket_id: u8,
+    pub family: String<5>,
+    pub remote_port: u16,
+    pub remote_address: String<{ MAX_IP_ADDRESS_LENGTH as usize }>,
+}
+
+impl AtatCmd<80> for Connect {
+    type Response = EmptyResponse;
+
+    const MAX_TIMEOUT_MS: u32 = 15000;
+
+    fn as_bytes(&self) -> Vec<u8, 80> {
+        let mut cmd = String::<80>::new();
+        write!(
+            cmd,
+            "AT+connect={},{},{},{}\r\n",
+            self.socket_id, self.family, self.remote_port, self.remote_address
+        )
+        .ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        resp?;
+
+        Ok(EmptyResponse)
+    }
+}
+
+#[derive(Debug, Clone, AtatResp, PartialEq)]
+pub struct SendResponse {
+    /// Socket identifier
+    #[at_arg(position = 0)]
+    pub socket_id: u8,
+    /// Number of bytes sent
+    #[at_arg(position = 1)]
+    pub length: u16,
+}
+
+/// Send data on a connected socket
+#[derive(AtatCmd)]
+#[at_cmd("+send", SendResponse, timeout_ms = 5000)]
+pub struct Send<'a> {
+    #[at_arg(position = 0)]
+    pub socket_id: u8,
+    /// Data format
+    #[at_arg(position = 1)]
+    pub format: DataFormat,
+    /// Length of the data
+    #[at_arg(position = 2)]
+    pub length: u16,
+    #[at_arg(position = 3, len = 1460)]
+    pub data: Payload<'a>,
+}
+
+/// Data received on a socket
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecvResponse {
+    pub data: Vec<u8, { MAX_PAYLOAD_SIZE as usize }>,
+}
+
+impl atat::AtatResp for RecvResponse {}
+
+/// Receive data from a connected socket
+///
+/// Response: `+recv:<id>,<format>,<length>,<data>`
+pub struct Recv {
+    pub socket_id: u8,
+    /// Data format
+    pub format: DataFormat,
+    /// Maximum number of bytes to receive
+    pub length: u16,
+}
+
+impl AtatCmd<32> for Recv {
+    type Response = RecvResponse;
+
+    const MAX_TIMEOUT_MS: u32 = 5000;
+
+    fn as_bytes(&self) -> Vec<u8, 32> {
+        let mut cmd = String::<32>::new();
+        write!(
+            cmd,
+            "AT+recv={},{},{}\r\n",
+            self.socket_id, self.format as u8, self.length
+        )
+        .ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+        let _socket_id: u8 = args.next()?;
+        let _format: u8 = args.next()?;
+        let length: usize = args.next()?;
+
+        Ok(RecvResponse {
+            data: Vec::from_slice(args.data(length)?)
+                .map_err(|_| Error::Parse)?,
+        })
+    }
+}
+
+/// Send a datagram to a remote address
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+sendTo",
+    SendResponse,
+    timeout_ms = 5000,
+    quote_escape_strings = false
+)]
+pub struct SendTo<'a> {
+    #[at_arg(position = 0)]
+    pub socket_id: u8,
+    #[at_arg(position = 1)]
+    pub family: String<5>,
+    #[at_arg(position = 2)]
+    pub remote_port: u16,
+    #[at_arg(position = 3)]
+    pub remote_address: String<{ MAX_IP_ADDRESS_LENGTH as usize }>,
+    /// Data format
+    #[at_arg(position = 4)]
+    pub format: DataFormat,
+    /// Length of the data
+    #[at_arg(position = 5)]
+    pub length: u16,
+    #[at_arg(position = 6, len = 1460)]
+    pub data: Payload<'a>,
+}
+
+/// Datagram received on a socket
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecvFromResponse {
+    /// Address of the sender
+    pub source: SocketAddr,
+    pub data: Vec<u8, { MAX_PAYLOAD_SIZE as usize }>,
+}
+
+impl atat::AtatResp for RecvFromResponse {}
+
+/// Receive a datagram together with its source address
+///
+/// Response:
+/// `+recvfrom:<id>,<family>,<port>,<address>,<format>,<length>,<data>`
+pub struct RecvFrom {
+    pub socket_id: u8,
+    /// Data format
+    pub format: DataFormat,
+    /// Maximum number of bytes to receive
+    pub length: u16,
+}
+
+impl AtatCmd<32> for RecvFrom {
+    type Response = RecvFromResponse;
+
+    const MAX_TIMEOUT_MS: u32 = 5000;
+
+    fn as_bytes(&self) -> Vec<u8, 32> {
+        let mut cmd = String::<32>::new();
+        write!(
+            cmd,
+            "AT+recvFrom={},{},{}\r\n",
+            self.socket_id, self.format as u8, self.length
+        )
+        .ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+        let _socket_id: u8 = args.next()?;
+        let _family = args.next_str()?;
+        let port = args.next()?;
+        let address: IpAddr = args.next()?;
+        let _format: u8 = args.next()?;
+        let length: usize = args.next()?;
+
+        Ok(RecvFromResponse {
+            source: SocketAddr::new(address, port),
+            data: Vec::from_slice(args.data(length)?)
+                .map_err(|_| Error::Parse)?,
+        })
+    }
+}
+
+/// Protocol level of a socket option.
+#[derive(Debug, Clone, Copy, AtatEnum)]
+pub enum Level {
+    /// Socket level options
+    Socket,
+    /// IP level options
+    Ip,
+    /// Physical layer options
+    Phy,
+}
+
+impl From<Level> for String<16> {
+    fn from(value: Level) -> Self {
+        String::from(match value {
+            Level::Socket => "SOL_SOCKET",
+            Level::Ip => "SL_IPPROTO_IP",
+            Level::Phy => "SL_SOL_PHY_OPT",
+        })
+    }
+}
+
+/// Socket options.
+#[derive(Debug, Clone, Copy, AtatEnum)]
+pub enum SocketOption {
+    /// TLS method, see [`TlsMethod`]
+    SecureMethod,
+    /// Allowed cipher suites, see [`CipherSuites`]
+    SecureMask,
+    /// CA certificate file used to verify the peer
+    SecureCaFile,
+    /// Certificate file presented to the peer
+    SecureCertificateFile,
+    /// Private key file of the certificate
+    SecurePrivateKeyFile,
+    /// Domain name checked against the peer certificate and sent as SNI
+    SecureDomainNameVerification,
+    /// Application protocols offered, see [`Alpn`]
+    SecureAlpn,
+    /// Send keep-alive messages, `0` or `1`
+    KeepAlive,
+    /// Keep-alive timeout in seconds
+    KeepAliveTime,
+    /// Receive timeout, `<seconds>,<microseconds>`
+    RecvTimeout,
+    /// Non-blocking mode, `0` or `1`
+    NonBlocking,
+    /// Receive buffer size in bytes
+    RecvBuffer,
+    /// Allow sending broadcast datagrams, `0` or `1`
+    Broadcast,
+    /// Linger on close, `<enabled>,<seconds>`
+    Linger,
+    /// Time-to-live of sent IPv4 multicast datagrams
+    MulticastTtl,
+    /// Join an IPv4 multicast group, `<group>,<interface>`
+    AddMembership,
+    /// Leave an IPv4 multicast group, `<group>,<interface>`
+    DropMembership,
+    /// Join an IPv6 multicast group, `<group>,<interface index>`
+    AddMembershipV6,
+    /// Leave an IPv6 multicast group, `<group>,<interface index>`
+    DropMembershipV6,
+}
+
+impl SocketOption {
+    /// Protocol level of the option.
+    pub const fn level(self) -> Level {
+        match self {
+            Self::SecureMethod
+            | Self::SecureMask
+            | Self::SecureCaFile
+            | Self::SecureCertificateFile
+            | Self::SecurePrivateKeyFile
+            | Self::SecureDomainNameVerification
+            | Self::SecureAlpn
+            | Self::KeepAlive
+            | Self::KeepAliveTime
+            | Self::RecvTimeout
+            | Self::NonBlocking
+            | Self::RecvBuffer
+            | Self::Broadcast
+            | Self::Linger => Level::Socket,
+            Self::MulticastTtl
+            | Self::AddMembership
+            | Self::DropMembership
+            | Self::AddMembershipV6
+            | Self::DropMembershipV6 => Level::Ip,
+        }
+    }
+}
+
+impl From<SocketOption> for String<40> {
+    fn from(value: SocketOption) -> Self {
+        String::from(match value {
+            SocketOption::SecureMethod => "SO_SECMETHOD",
+            SocketOption::SecureMask => "SO_SECURE_MASK",
+            SocketOption::SecureCaFile => "SO_SECURE_FILES_CA_FILE_NAME",
+            SocketOption::SecureCertificateFile => {
+                "SO_SECURE_FILES_CERTIFICATE_FILE_NAME"
+            }
+            SocketOption::SecurePrivateKeyFile => {
+                "SO_SECURE_FILES_PRIVATE_KEY_FILE_NAME"
+            }
+            SocketOption::SecureDomainNameVerification => {
+                "SO_SECURE_DOMAIN_NAME_VERIFICATION"
+            }
+            SocketOption::SecureAlpn => "SO_SECURE_ALPN",
+            SocketOption::KeepAlive => "SO_KEEPALIVE",
+            SocketOption::KeepAliveTime => "SO_KEEPALIVETIME",
+            SocketOption::RecvTimeout => "SO_RCVTIMEO",
+            SocketOption::NonBlocking => "SO_NONBLOCKING",
+            SocketOption::RecvBuffer => "SO_RCVBUF",
+            SocketOption::Broadcast => "SO_BROADCAST",
+            SocketOption::Linger => "SO_LINGER",
+            SocketOption::MulticastTtl => "IP_MULTICAST_TTL",
+            SocketOption::AddMembership => "IP_ADD_MEMBERSHIP",
+            SocketOption::DropMembership => "IP_DROP_MEMBERSHIP",
+            SocketOption::AddMembershipV6 => "IPV6_ADD_MEMBERSHIP",
+            SocketOption::DropMembershipV6 => "IPV6_DROP_MEMBERSHIP",
+        })
+    }
+}
+
+/// Set a socket option
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+setSockOpt",
+    EmptyResponse,
+    timeout_ms = 100,
+    quote_escape_strings = false
+)]
+pub struct SetSockOpt {
+    #[at_arg(position = 0)]
+    pub socket_id: u8,
+    #[at_arg(position = 1)]
+    pub level: String<16>,
+    #[at_arg(position = 2)]
+    pub option: String<40>,
+    #[at_arg(position = 3)]
+    pub value: String<1024>,
+}
+
+/// Value of a socket option
+#[derive(Debug, Clone, PartialEq)]
+pub struct SockOptValue {
+    /// Option value as reported by the module
+    pub value: String<64>,
+}
+
+impl atat::AtatResp for SockOptValue {}
+
+/// Get a socket option
+///
+/// Response: `+getsockopt:<value>`
+pub struct GetSockOpt {
+    pub socket_id: u8,
+    pub level: String<16>,
+    pub option: String<40>,
+}
+
+impl AtatCmd<80> for GetSockOpt {
+    type Response = SockOptValue;
+
+    const MAX_TIMEOUT_MS: u32 = 100;
+
+    fn as_bytes(&self) -> Vec<u8, 80> {
+        let mut cmd = String::<80>::new();
+        write!(
+            cmd,
+            "AT+getSockOpt={},{},{}\r\n",
+            self.socket_id, self.level, self.option
+        )
+        .ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut value = String::new();
+        value
+            .push_str(Args::new(resp?).rest()?)
+            .map_err(|_| Error::Parse)?;
+
+        Ok(SockOptValue { value })
+    }
+}
+
+/// TLS protocol version of a secure socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
+pub enum TlsMethod {
+    Sslv3,
+    Tlsv1,
+    Tlsv1_1,
+    Tlsv1_2,
+    /// Negotiate any version from SSLv3 to TLSv1.2
+    Sslv3Tlsv1_2,
+}
+
+impl From<TlsMethod> for String<1024> {
+    fn from(value: TlsMethod) -> Self {
+        String::from(match value {
+            TlsMethod::Sslv3 => "SSLV3",
+            TlsMethod::Tlsv1 => "TLSV1",
+            TlsMethod::Tlsv1_1 => "TLSV1_1",
+            TlsMethod::Tlsv1_2 => "TLSV1_2",
+            TlsMethod::Sslv3Tlsv1_2 => "SSLV3_TLSV1_2",
+        })
+    }
+}
+
+/// Cipher suites allowed on a secure socket.
+///
+/// Suites are combined with `|`, e.g.
+/// `CipherSuites::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256 |
+/// CipherSuites::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CipherSuites(u32);
+
+impl CipherSuites {
+    pub const SSL_RSA_WITH_RC4_128_SHA: Self = Self(1 << 0);
+    pub const SSL_RSA_WITH_RC4_128_MD5: Self = Self(1 << 1);
+    pub const TLS_RSA_WITH_AES_256_CBC_SHA: Self = Self(1 << 2);
+    pub const TLS_DHE_RSA_WITH_AES_256_CBC_SHA: Self = Self(1 << 3);
+    pub const TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA: Self = Self(1 << 4);
+    pub const TLS_ECDHE_RSA_WITH_RC4_128_SHA: Self = Self(1 << 5);
+    pub const TLS_RSA_WITH_AES_128_CBC_SHA256: Self = Self(1 << 6);
+    pub const TLS_RSA_WITH_AES_256_CBC_SHA256: Self = Self(1 << 7);
+    pub const TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256: Self = Self(1 << 8);
+    pub const TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256: Self = Self(1 << 9);
+    pub const TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA: Self = Self(1 << 10);
+    pub const TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA: Self = Self(1 << 11);
+    pub const TLS_RSA_WITH_AES_128_GCM_SHA256: Self = Self(1 << 12);
+    pub const TLS_RSA_WITH_AES_256_GCM_SHA384: Self = Self(1 << 13);
+    pub const TLS_DHE_RSA_WITH_AES_128_GCM_SHA256: Self = Self(1 << 14);
+    pub const TLS_DHE_RSA_WITH_AES_256_GCM_SHA384: Self = Self(1 << 15);
+    pub const TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256: Self = Self(1 << 16);
+    pub const TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384: Self = Self(1 << 17);
+    pub const TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256: Self = Self(1 << 18);
+    pub const TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384: Self = Self(1 << 19);
+    pub const TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256: Self =
+        Self(1 << 20);
+    pub const TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256: Self = Self(1 << 21);
+    pub const TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256: Self = Self(1 << 22);
+
+    const NAMES: [(Self, &'static str); 23] = [
+        (Self::SSL_RSA_WITH_RC4_128_SHA, "SSL_RSA_WITH_RC4_128_SHA"),
+        (Self::SSL_RSA_WITH_RC4_128_MD5, "SSL_RSA_WITH_RC4_128_MD5"),
+        (
+            Self::TLS_RSA_WITH_AES_256_CBC_SHA,
+            "TLS_RSA_WITH_AES_256_CBC_SHA",
+        ),
+        (
+            Self::TLS_DHE_RSA_WITH_AES_256_CBC_SHA,
+            "TLS_DHE_RSA_WITH_AES_256_CBC_SHA",
+        ),
+        (
+            Self::TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA,
+            "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA",
+        ),
+        (
+            Self::TLS_ECDHE_RSA_WITH_RC4_128_SHA,
+            "TLS_ECDHE_RSA_WITH_RC4_128_SHA",
+        ),
+        (
+            Self::TLS_RSA_WITH_AES_128_CBC_SHA256,
+            "TLS_RSA_WITH_AES_128_CBC_SHA256",
+        ),
+        (
+            Self::TLS_RSA_WITH_AES_256_CBC_SHA256,
+            "TLS_RSA_WITH_AES_256_CBC_SHA256",
+        ),
+        (
+            Self::TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256,
+            "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256",
+        ),
+        (
+            Self::TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256,
+            "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256",
+        ),
+        (
+            Self::TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA,
+            "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA",
+        ),
+        (
+            Self::TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA,
+            "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA",
+        ),
+        (
+            Self::TLS_RSA_WITH_AES_128_GCM_SHA256,
+            "TLS_RSA_WITH_AES_128_GCM_SHA256",
+        ),
+        (
+            Self::TLS_RSA_WITH_AES_256_GCM_SHA384,
+            "TLS_RSA_WITH_AES_256_GCM_SHA384",
+        ),
+        (
+            Self::TLS_DHE_RSA_WITH_AES_128_GCM_SHA256,
+            "TLS_DHE_RSA_WITH_AES_128_GCM_SHA256",
+        ),
+        (
+            Self::TLS_DHE_RSA_WITH_AES_256_GCM_SHA384,
+            "TLS_DHE_RSA_WITH_AES_256_GCM_SHA384",
+        ),
+        (
+            Self::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+            "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+        ),
+        (
+            Self::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+        ),
+        (
+            Self::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+            "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256",
+        ),
+        (
+            Self::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+            "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384",
+        ),
+        (
+            Self::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+            "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256",
+        ),
+        (
+            Self::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        ),
+        (
+            Self::TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            "TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        ),
+    ];
+
+    /// Returns true if all suites in `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for CipherSuites {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl From<CipherSuites> for String<1024> {
+    fn from(value: CipherSuites) -> Self {
+        let mut suites = String::new();
+        for (suite, name) in CipherSuites::NAMES {
+            if value.contains(suite) {
+                if !suites.is_empty() {
+                    suites.push('|').ok();
+                }
+                suites.push_str(name).ok();
+            }
+        }
+        suites
+    }
+}
+
+/// Application protocols offered on a secure socket.
+///
+/// Protocols are combined with `|`, e.g. `Alpn::H1 | Alpn::H2`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Alpn(u8);
+
+impl Alpn {
+    /// HTTP/1.1
+    pub const H1: Self = Self(1 << 0);
+    /// HTTP/2 over TLS
+    pub const H2: Self = Self(1 << 1);
+    /// HTTP/2 over cleartext TCP
+    pub const H2C: Self = Self(1 << 2);
+    /// HTTP/2 draft 14
+    pub const H2_14: Self = Self(1 << 3);
+    /// HTTP/2 draft 16
+    pub const H2_16: Self = Self(1 << 4);
+    /// All protocols supported by the module
+    pub const FULL_LIST: Self = Self(1 << 5);
+
+    const NAMES: [(Self, &'static str); 6] = [
+        (Self::H1, "H1"),
+        (Self::H2, "H2"),
+        (Self::H2C, "H2C"),
+        (Self::H2_14, "H2_14"),
+        (Self::H2_16, "H2_16"),
+        (Self::FULL_LIST, "FULL_LIST"),
+    ];
+
+    /// Returns true if all protocols in `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Alpn {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl From<Alpn> for String<1024> {
+    fn from(value: Alpn) -> Self {
+        let mut protocols = String::new();
+        for (protocol, name) in Alpn::NAMES {
+            if value.contains(protocol) {
+                if !protocols.is_empty() {
+                    protocols.push('|').ok();
+                }
+                protocols.push_str(name).ok();
+            }
+        }
+        protocols
+    }
+}
+
+/// Set of socket identifiers.
+///
+/// Sockets are combined with [`SocketSet::insert`] and sent to the module
+/// separated by `|`, e.g. `0|3`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SocketSet(u16);
+
+impl SocketSet {
+    /// Highest socket identifier plus one.
+    pub const CAPACITY: u8 = 16;
+
+    /// Creates an empty set.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Add a socket to the set. Identifiers out of range are ignored.
+    pub fn insert(&mut self, socket_id: u8) {
+        if socket_id < Self::CAPACITY {
+            self.0 |= 1 << socket_id;
+        }
+    }
+
+    /// Returns true if the socket is in the set.
+    pub const fn contains(self, socket_id: u8) -> bool {
+        socket_id < Self::CAPACITY && self.0 & (1 << socket_id) != 0
+    }
+
+    /// Returns true if the set is empty.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Sockets in the set, in ascending order.
+    pub fn iter(self) -> impl Iterator<Item = u8> {
+        (0..Self::CAPACITY).filter(move |&id| self.contains(id))
+    }
+
+    /// Parses identifiers separated by `|`.
+    fn parse(s: &str) -> Result<Self, Error> {
+        let mut set = Self::new();
+        for id in s.split('|').map(str::trim).filter(|id| !id.is_empty()) {
+            set.insert(id.parse().map_err(|_| Error::Parse)?);
+        }
+        Ok(set)
+    }
+}
+
+impl From<SocketSet> for String<48> {
+    fn from(value: SocketSet) -> Self {
+        let mut ids = String::new();
+        for id in value.iter() {
+            if !ids.is_empty() {
+                ids.push('|').ok();
+            }
+            write!(ids, "{}", id).ok();
+        }
+        ids
+    }
+}
+
+/// Sockets ready for reading or writing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SelectResponse {
+    /// Sockets with data to receive or, for listeners, pending connections
+    pub read: SocketSet,
+    /// Sockets able to send
+    pub write: SocketSet,
+}
+
+impl atat::AtatResp for SelectResponse {}
+
+/// Wait until sockets are ready for reading or writing
+///
+/// Response: `+select:<read ids>,<write ids>`
+pub struct Select {
+    pub read: SocketSet,
+    pub write: SocketSet,
+    pub timeout_secs: u32,
+    pub timeout_usecs: u32,
+}
+
+impl AtatCmd<128> for Select {
+    type Response = SelectResponse;
+
+    const MAX_TIMEOUT_MS: u32 = MAX_SELECT_TIMEOUT_MS + 1000;
+
+    fn as_bytes(&self) -> Vec<u8, 128> {
+        let mut cmd = String::<128>::new();
+        write!(
+            cmd,
+            "AT+select={},{},{},{}\r\n",
+            String::<48>::from(self.read),
+            String::<48>::from(self.write),
+            self.timeout_secs,
+            self.timeout_usecs
+        )
+        .ok();
+        cmd.into_bytes()
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], InternalError>,
+    ) -> Result<Self::Response, Error> {
+        let mut args = Args::new(resp?);
+
+        Ok(SelectResponse {
+            read: SocketSet::parse(args.next_str().unwrap_or_default())?,
+            write: SocketSet::parse(args.next_str().unwrap_or_default())?,
+        })
+    }
+}
+
+/// Longest timeout of a single select command.
+pub const MAX_SELECT_TIMEOUT_MS: u32 = 10000;
+
+/// Kind of an asynchronous socket notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsyncEventKind {
+    /// TLS handshake of an accepted connection failed
+    SslAccept,
+    /// Received record was larger than the receive buffer
+    RxFragmentationTooBig,
+    /// Peer closed the connection without a TLS close notification
+    PeerClosed,
+    /// Secure connection established
+    ConnectedSecured,
+    /// Any other notification
+    Other,
+}
+
+impl AsyncEventKind {
+    fn parse(s: &str) -> Self {
+        if s.eq_ignore_ascii_case("SSL_ACCEPT") {
+            Self::SslAccept
+        } else if s.eq_ignore_ascii_case("RX_FRAGMENTATION_TOO_BIG") {
+            Self::RxFragmentationTooBig
+        } else if s
+            .eq_ignore_ascii_case("OTHER_SIDE_CLOSE_SSL_DATA_NOT_ENCRYPTED")
+        {
+            Self::PeerClosed
+        } else if s.eq_ignore_ascii_case("CONNECTED_SECURED") {
+            Self::ConnectedSecured
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Socket event
+///
+/// Event: `+eventsocket:<event>,<id>,...`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Sending failed, e.g. because the transmit buffers are full
+    ///
+    /// Event: `+eventsocket:TXFAILED,<id>,<error>`
+    TxFailed {
+        socket_id: u8,
+        /// Module error code
+        error: i32,
+    },
+    /// Asynchronous notification
+    ///
+    /// Event: `+eventsocket:ASYNCEVENT,<id>,<kind>`
+    AsyncEvent { socket_id: u8, kind: AsyncEventKind },
+}
+
+impl Event {
+    pub(crate) fn parse(resp: &[u8]) -> Option<Self> {
+        let mut args = Args::new(resp);
+        let event = args.next_str().ok()?;
+        let socket_id = args.next().ok()?;
+
+        if event.eq_ignore_ascii_case("txfailed") {
+            Some(Event::TxFailed {
+                socket_id,
+                error: args.next().ok()?,
+            })
+        } else if event.eq_ignore_ascii_case("asyncevent") {
+            Some(Event::AsyncEvent {
+                socket_id,
+                kind: AsyncEventKind::parse(args.next_str().ok()?),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Socket the event refers to.
+    pub const fn socket_id(&self) -> u8 {
+        match self {
+            Self::TxFailed { socket_id, .. }
+            | Self::AsyncEvent { socket_id, .. } => *socket_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_payload() {
+        let data = b"a,\"b\"\r\n";
+        let cmd = Send {
+            socket_id: 1,
+            format: DataFormat::Binary,
+            length: data.len() as u16,
+            data: Payload(data),
+        };
+
+        assert_eq!(&cmd.as_bytes()[..], b"AT+send=1,0,7,a,\"b\"\r\n\r\n");
+    }
+
+    #[test]
+    fn tx_failed_event() {
+        assert_eq!(
+            Event::parse(b"+eventsocket:TXFAILED,2,-105"),
+            Some(Event::TxFailed {
+                socket_id: 2,
+                error: -105
+            })
+        );
+    }
+
+    #[test]
+    fn async_event() {
+        let event = Event::parse(b"+eventsocket:AsyncEvent,3,SSL_ACCEPT");
+
+        assert_eq!(
+            event,
+            Some(Event::AsyncEvent {
+                socket_id: 3,
+                kind: AsyncEventKind::SslAccept
+            })
+        );
+        assert_eq!(event.map(|event| event.socket_id()), Some(3));
+        assert_eq!(
+            Event::parse(b"+eventsocket:asyncevent,3,SOMETHING"),
+            Some(Event::AsyncEvent {
+                socket_id: 3,
+                kind: AsyncEventKind::Other
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_events() {
+        for event in [
+            &b"+eventsocket:TXFAILED"[..],
+            b"+eventsocket:TXFAILED,x,-105",
+            b"+eventsocket:TXFAILED,1",
+            b"+eventsocket:UNKNOWN,1,2",
+        ] {
+            assert_eq!(Event::parse(event), None);
+        }
+    }
 }
@@ -1,7 +1,19 @@
-use super::EmptyResponse;
-use atat::atat_derive::{AtatCmd, AtatEnum};
+use super::{args::Args, EmptyResponse, Escaped};
+use atat::atat_derive::{AtatCmd, AtatEnum, AtatResp};
 use heapless::String;
 
+/// Maximum length of an SSID.
+pub const MAX_SSID_LENGTH: usize = 32;
+
+/// Length of a BSSID, e.g. `aa:bb:cc:dd:ee:ff`.
+pub const MAX_BSSID_LENGTH: usize = 17;
+
+/// Maximum length of a security key, e.g. a hex encoded WPA key.
+pub const MAX_SECURITY_KEY_LENGTH: usize = 64;
+
+/// Maximum length of an enterprise user name.
+pub const MAX_USER_LENGTH: usize = 63;
+
 #[derive(Debug, Clone, Copy, AtatEnum)]
 pub enum Mode {
     /// Station mode
@@ -12,9 +24,9 @@ pub enum Mode {
     P2p,
 }
 
-impl Into<String<3>> for Mode {
-    fn into(self) -> String<3> {
-        String::from(match self {
+impl From<Mode> for String<3> {
+    fn from(value: Mode) -> Self {
+        String::from(match value {
             Mode::Sta => "STA",
             Mode::Ap => "AP",
             Mode::P2p => "P2P",
@@ -48,7 +60,7 @@ pub struct Scan {
     pub count: u8,
 }
 
-#[derive(Debug, Clone, Copy, AtatEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
 pub enum SecurityType {
     Open,
     Wep,
@@ -61,9 +73,9 @@ pub enum SecurityType {
     WpsPin,
 }
 
-impl Into<String<10>> for SecurityType {
-    fn into(self) -> String<10> {
-        String::from(match self {
+impl From<SecurityType> for String<10> {
+    fn from(value: SecurityType) -> Self {
+        String::from(match value {
             SecurityType::Open => "OPEN",
             SecurityType::Wep => "WEP",
             SecurityType::WepShared => "WEP_SHARED",
@@ -71,13 +83,13 @@ impl Into<String<10>> for SecurityType {
             SecurityType::Wpa2Plus => "WPA2_PLUS",
             SecurityType::Wpa3 => "WPA3",
             SecurityType::WpaEnt => "WPA_ENT",
-            SecurityType::WpsPbc => "WPA_PBC",
-            SecurityType::WpsPin => "WPA_PIN",
+            SecurityType::WpsPbc => "WPS_PBC",
+            SecurityType::WpsPin => "WPS_PIN",
         })
     }
 }
 
-#[derive(Debug, Clone, Copy, AtatEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
 pub enum SecurityEapType {
     Tls,
     TtlsTls,
@@ -90,9 +102,9 @@ pub enum SecurityEapType {
     Peap1Psk,
 }
 
-impl Into<String<14>> for SecurityEapType {
-    fn into(self) -> String<14> {
-        String::from(match self {
+impl From<SecurityEapType> for String<14> {
+    fn from(value: SecurityEapType) -> Self {
+        String::from(match value {
             SecurityEapType::Tls => "TLS",
             SecurityEapType::TtlsTls => "TTLS_TLS",
             SecurityEapType::TtlsMschapv2 => "TTLS_MSCHAPv2",
@@ -106,6 +118,10 @@ impl Into<String<14>> for SecurityEapType {
     }
 }
 
+/// Connect to an access point
+///
+/// Optional arguments are sent empty, e.g.
+/// `AT+wlanConnect=home,,WPA_WPA2,secret123,,,`.
 #[derive(AtatCmd)]
 #[at_cmd(
     "+wlanConnect",
@@ -113,53 +129,70 @@ impl Into<String<14>> for SecurityEapType {
     timeout_ms = 100,
     quote_escape_strings = false
 )]
-pub struct Connect {
+pub struct Connect<'a> {
     /// SSID name
-    #[at_arg(position = 0)]
-    pub ssid: String<32>,
-    /// Optional mac address
-    #[at_arg(position = 1)]
-    pub bssid: Option<String<17>>,
+    #[at_arg(position = 0, len = 66)]
+    pub ssid: Escaped<'a>,
+    /// Mac address, empty for any
+    #[at_arg(position = 1, len = 36)]
+    pub bssid: Escaped<'a>,
     /// Security type
     pub security_type: String<10>,
-    /// Security key
-    pub security_key: Option<String<63>>,
-    /// Enterprise user name
-    pub security_ext_user: Option<String<63>>,
-    /// Enterprise anonymous user name
-    pub security_ext_anon_user: Option<String<63>>,
-    /// Enterprise EAP protocol
-    pub security_ext_eap_method: Option<String<14>>,
+    /// Security key, empty if unused
+    #[at_arg(len = 130)]
+    pub security_key: Escaped<'a>,
+    /// Enterprise user name, empty if unused
+    #[at_arg(len = 128)]
+    pub security_ext_user: Escaped<'a>,
+    /// Enterprise anonymous user name, empty if unused
+    #[at_arg(len = 128)]
+    pub security_ext_anon_user: Escaped<'a>,
+    /// Enterprise EAP protocol, empty if unused
+    pub security_ext_eap_method: String<14>,
 }
 
 #[derive(AtatCmd)]
 #[at_cmd("+wlanDisconnect", EmptyResponse, timeout_ms = 100)]
 pub struct Disconnect {}
 
+#[derive(Debug, Clone, AtatResp, PartialEq)]
+pub struct ProfileAddResponse {
+    /// Index of the stored profile
+    #[at_arg(position = 0)]
+    pub index: u8,
+}
+
+/// Store a profile used to connect automatically
+///
+/// Optional arguments are sent empty, e.g.
+/// `AT+wlanProfileAdd=home,,WPA_WPA2,secret123,,,,1`.
 #[derive(AtatCmd)]
 #[at_cmd(
     "+wlanProfileAdd",
-    EmptyResponse,
+    ProfileAddResponse,
     timeout_ms = 100,
     quote_escape_strings = false
 )]
-pub struct ProfileAdd {
+pub struct ProfileAdd<'a> {
     /// SSID name
-    #[at_arg(position = 0)]
-    pub ssid: String<32>,
-    /// Optional mac address
-    #[at_arg(position = 1)]
-    pub bssid: Option<String<17>>,
+    #[at_arg(position = 0, len = 66)]
+    pub ssid: Escaped<'a>,
+    /// Mac address, empty for any
+    #[at_arg(position = 1, len = 36)]
+    pub bssid: Escaped<'a>,
     /// Security type
     pub security_type: String<10>,
-    /// Security key
-    pub security_key: Option<String<63>>,
-    /// Enterprise user name
-    pub security_ext_user: Option<String<63>>,
-    /// Enterprise anonymous user name
-    pub security_ext_anon_user: Option<String<63>>,
-    /// Enterprise EAP protocol
-    pub security_ext_eap_method: Option<String<14>>,
+    /// Security key, empty if unused
+    #[at_arg(len = 130)]
+    pub security_key: Escaped<'a>,
+    /// Enterprise user name, empty if unused
+    #[at_arg(len = 128)]
+    pub security_ext_user: Escaped<'a>,
+    /// Enterprise anonymous user name, empty if unused
+    #[at_arg(len = 128)]
+    pub security_ext_anon_user: Escaped<'a>,
+    /// Enterprise EAP protocol, empty if unused
+    pub security_ext_eap_method: String<14>,
     /// Profile priority
     pub priority: u8,
 }
@@ -187,3 +220,157 @@ pub struct ProfileDelete {
     /// Index
     index: u8,
 }
+
+/// General WLAN setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtatEnum)]
+pub enum GeneralOption {
+    /// Do not verify the enterprise server certificate, `0` or `1`
+    DisableEntServerAuth,
+}
+
+impl From<GeneralOption> for String<24> {
+    fn from(value: GeneralOption) -> Self {
+        String::from(match value {
+            GeneralOption::DisableEntServerAuth => "DISABLE_ENT_SERVER_AUTH",
+        })
+    }
+}
+
+/// Change a general WLAN setting
+///
+/// Command: `AT+wlanSet=general,<option>,<value>`
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+wlanSet=general,",
+    EmptyResponse,
+    timeout_ms = 100,
+    value_sep = false,
+    quote_escape_strings = false
+)]
+pub struct SetGeneral<'a> {
+    /// Option
+    #[at_arg(position = 0)]
+    pub option: String<24>,
+    /// Option value
+    #[at_arg(position = 1, len = 258)]
+    pub value: Escaped<'a>,
+}
+
+/// Access point of a WLAN connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    /// SSID name
+    pub ssid: String<MAX_SSID_LENGTH>,
+    /// Mac address of the access point
+    pub bssid: String<MAX_BSSID_LENGTH>,
+}
+
+impl Connection {
+    /// `<ssid>,<bssid>`, the SSID may contain commas.
+    fn parse(args: &str) -> Option<Self> {
+        let (ssid, bssid) = args.rsplit_once(',')?;
+
+        Some(Self {
+            ssid: ssid.trim().parse().ok()?,
+            bssid: bssid.trim().parse().ok()?,
+        })
+    }
+}
+
+/// WLAN event
+///
+/// Event: `+eventwlan:<event>,...`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Connected to an access point
+    ///
+    /// Event: `+eventwlan:connect,<ssid>,<bssid>`
+    Connect(Connection),
+    /// Disconnected from an access point, also sent when connecting fails
+    ///
+    /// Event: `+eventwlan:disconnect,<ssid>,<bssid>,<reason>`
+    Disconnect {
+        connection: Connection,
+        /// Disconnect reason code
+        reason: u16,
+    },
+    /// Any other event, e.g. provisioning or peer-to-peer events
+    Other,
+}
+
+impl Event {
+    pub(crate) fn parse(resp: &[u8]) -> Self {
+        Self::parse_known(resp).unwrap_or(Event::Other)
+    }
+
+    fn parse_known(resp: &[u8]) -> Option<Self> {
+        let mut args = Args::new(resp);
+        let event = args.next_str().ok()?;
+        let rest = args.rest().ok()?;
+
+        if event.eq_ignore_ascii_case("connect") {
+            Some(Event::Connect(Connection::parse(rest)?))
+        } else if event.eq_ignore_ascii_case("disconnect") {
+            let (connection, reason) = rest.rsplit_once(',')?;
+            Some(Event::Disconnect {
+                connection: Connection::parse(connection)?,
+                reason: reason.trim().parse().ok()?,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connect_event() {
+        assert_eq!(
+            Event::parse(b"+eventwlan:connect,home,aa:bb:cc:dd:ee:ff"),
+            Event::Connect(Connection {
+                ssid: "home".into(),
+                bssid: "aa:bb:cc:dd:ee:ff".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn ssid_with_commas() {
+        assert_eq!(
+            Event::parse(b"+eventwlan:connect,a,b,c,aa:bb:cc:dd:ee:ff"),
+            Event::Connect(Connection {
+                ssid: "a,b,c".into(),
+                bssid: "aa:bb:cc:dd:ee:ff".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn disconnect_event() {
+        assert_eq!(
+            Event::parse(b"+eventwlan:DISCONNECT,home,aa:bb:cc:dd:ee:ff,15"),
+            Event::Disconnect {
+                connection: Connection {
+                    ssid: "home".into(),
+                    bssid: "aa:bb:cc:dd:ee:ff".into(),
+                },
+                reason: 15,
+            }
+        );
+    }
+
+    #[test]
+    fn other_events() {
+        for event in [
+            &b"+eventwlan:provisioning,1"[..],
+            b"+eventwlan:connect",
+            b"+eventwlan:disconnect,home,aa:bb:cc:dd:ee:ff,x",
+            b"+eventwlan:connect,0123456789012345678901234567890123,aa",
+        ] {
+            assert_eq!(Event::parse(event), Event::Other);
+        }
+    }
+}
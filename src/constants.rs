@@ -1,4 +1,6 @@
-/// Maximum recommended payload size.
+/// Maximum payload size of a single command.
+///
+/// Larger socket sends are split by [`crate::Socket::send_all`].
 pub const MAX_PAYLOAD_SIZE: u16 = 1460;
 
 /// Default receive buffer size.
@@ -13,5 +15,20 @@ pub const MAX_IP_ADDRESS_LENGTH: u8 = 44;
 /// Maximum host name length (e.g. URLs or IP addresses).
 pub const MAX_HOST_NAME_LENGTH: u8 = 128;
 
+/// Maximum length of a file name in the module file system.
+pub const MAX_FILE_NAME_LENGTH: u8 = 180;
+
 /// Maximum length of response text.
 pub const MAX_RESPONSE_TEXT_LENGTH: u16 = MAX_LINE_SIZE;
+
+/// Number of SNTP servers the module can be configured with.
+pub const MAX_SNTP_SERVERS: u8 = 3;
+
+/// Size of a storage block in the module file system.
+pub const STORAGE_BLOCK_SIZE: u32 = 4096;
+
+/// Number of storage blocks of the module's 4 MiB serial flash.
+///
+/// Part of the flash holds the file system metadata and system files, so
+/// fewer blocks are available to files.
+pub const SERIAL_FLASH_BLOCKS: u32 = 4 * 1024 * 1024 / STORAGE_BLOCK_SIZE;
@@ -0,0 +1,58 @@
+/// CRC-32 (IEEE 802.3) checksum, as used by zlib and Ethernet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    value: u32,
+}
+
+impl Crc32 {
+    /// Creates a new checksum.
+    pub const fn new() -> Self {
+        Self { value: 0xFFFF_FFFF }
+    }
+
+    /// Adds data to the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.value ^= u32::from(*byte);
+            for _ in 0..8 {
+                let mask = (self.value & 1).wrapping_neg();
+                self.value = (self.value >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+    }
+
+    /// Checksum of all data added so far.
+    pub const fn finish(&self) -> u32 {
+        !self.value
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn chunked() {
+        let mut crc = Crc32::default();
+        crc.update(b"1234");
+        crc.update(b"");
+        crc.update(b"56789");
+
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+        assert_eq!(Crc32::new().finish(), 0);
+    }
+}
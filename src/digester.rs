@@ -0,0 +1,313 @@
+use crate::command::{self, Urc};
+use crate::MAX_PAYLOAD_SIZE;
+use atat::digest::{DigestResult, Digester};
+use atat::AtDigester;
+
+/// Kind of a frame carrying a data payload.
+#[derive(Clone, Copy)]
+enum Kind {
+    /// Response followed by `OK`
+    Response,
+    /// Unsolicited event
+    Urc,
+}
+
+/// Lines ending in a length-prefixed payload.
+///
+/// Each entry is the line prefix, the index of the argument holding the
+/// payload length, which is directly followed by the payload, and the kind of
+/// the line.
+const FRAMES: [(&[u8], usize, Kind); 6] = [
+    (b"+recv:", 2, Kind::Response),
+    (b"+recvfrom:", 5, Kind::Response),
+    (b"+fileread:", 1, Kind::Response),
+    (b"+httpgetheader:", 1, Kind::Response),
+    (b"+httpreadresbody:", 3, Kind::Response),
+    (b"+eventmqtt:recv,", 5, Kind::Urc),
+];
+
+/// Trailer of a successful response.
+const OK: &[u8] = b"OK\r\n";
+
+/// Result of measuring a frame.
+enum Frame {
+    /// Length of the complete frame
+    Complete(usize),
+    /// More data is needed
+    Incomplete,
+    /// Not a frame with a payload, e.g. an error
+    Invalid,
+}
+
+/// Digester for the Calypso AT protocol.
+///
+/// The default digester splits responses and events at line endings, which
+/// breaks on payloads containing `\r\n` or `OK`. This digester reads the
+/// length of received socket data, file contents, HTTP headers and bodies and
+/// MQTT messages and takes exactly that many payload bytes, so binary data
+/// is transported intact. Events arriving between a payload and its `OK` are
+/// delivered as events. Everything else is handled like the default
+/// digester, with [`command::error_response`] matching error reasons.
+///
+/// A frame is only digested once it is complete, so the ingress buffer has
+/// to hold at least [`MIN_INGRESS_BUF_SIZE`](Self::MIN_INGRESS_BUF_SIZE)
+/// bytes.
+pub struct CalypsoDigester {
+    inner: AtDigester<Urc>,
+    /// A payload response was delivered before its `OK`
+    expect_ok: bool,
+}
+
+impl CalypsoDigester {
+    /// Smallest ingress buffer that fits a frame with a maximum size payload
+    /// in base64, plus its header, e.g. an MQTT topic, and trailer.
+    pub const MIN_INGRESS_BUF_SIZE: usize =
+        4 * (MAX_PAYLOAD_SIZE as usize).div_ceil(3) + 256;
+
+    /// Creates a new digester.
+    pub fn new() -> Self {
+        Self {
+            inner: AtDigester::new().with_custom_error(command::error_response),
+            expect_ok: false,
+        }
+    }
+}
+
+impl Default for CalypsoDigester {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Digester for CalypsoDigester {
+    fn digest<'a>(&mut self, input: &'a [u8]) -> (DigestResult<'a>, usize) {
+        let start = input
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(input.len());
+        let buf = &input[start..];
+
+        if self.expect_ok {
+            if starts_with_ignore_case(buf, OK) {
+                self.expect_ok = false;
+                return (DigestResult::None, start + OK.len());
+            }
+            if OK.starts_with(buf) {
+                return (DigestResult::None, 0);
+            }
+        }
+
+        for (prefix, length_index, kind) in FRAMES {
+            if !starts_with_ignore_case(buf, prefix) {
+                continue;
+            }
+
+            let len = match measure(buf, prefix.len(), length_index) {
+                Frame::Complete(len) => len,
+                Frame::Incomplete => return (DigestResult::None, 0),
+                Frame::Invalid => break,
+            };
+            let frame = &buf[..len];
+
+            match kind {
+                // the line ending is left for whatever follows, events are
+                // only matched after one
+                Kind::Urc => return (DigestResult::Urc(frame), start + len),
+                Kind::Response => {
+                    let trailer = skip_line_ending(buf, len);
+                    let rest = &buf[trailer..];
+                    if rest.len() < OK.len() {
+                        if OK.starts_with(rest) {
+                            return (DigestResult::None, 0);
+                        }
+                        break;
+                    }
+                    if rest.starts_with(OK) {
+                        return (
+                            DigestResult::Response(Ok(frame)),
+                            start + trailer + OK.len(),
+                        );
+                    }
+
+                    // something else, e.g. an event, arrived before the OK
+                    self.expect_ok = true;
+                    return (DigestResult::Response(Ok(frame)), start + len);
+                }
+            }
+        }
+
+        let result = self.inner.digest(input);
+        if matches!(result.0, DigestResult::Response(_)) {
+            self.expect_ok = false;
+        }
+
+        result
+    }
+}
+
+/// Measure a line whose argument at `length_index` is the length of the
+/// payload that follows it.
+fn measure(buf: &[u8], prefix_len: usize, length_index: usize) -> Frame {
+    let mut position = prefix_len;
+
+    for index in 0..=length_index {
+        let rest = &buf[position..];
+        let Some(end) = argument_end(rest) else {
+            return Frame::Incomplete;
+        };
+        if rest[end] == b'\r' {
+            return Frame::Invalid;
+        }
+
+        if index == length_index {
+            let length = core::str::from_utf8(&rest[..end])
+                .ok()
+                .and_then(|length| length.trim().parse::<usize>().ok());
+            let Some(length) = length else {
+                return Frame::Invalid;
+            };
+
+            let len = position + end + 1 + length;
+            return if buf.len() < len {
+                Frame::Incomplete
+            } else {
+                Frame::Complete(len)
+            };
+        }
+
+        position += end + 1;
+    }
+
+    Frame::Invalid
+}
+
+/// Index of the separator or line ending after the argument at the start of
+/// `buf`, skipping separators inside double quotes.
+fn argument_end(buf: &[u8]) -> Option<usize> {
+    let mut quoted = false;
+    let mut escaped = false;
+
+    buf.iter().position(|&b| {
+        if quoted {
+            quoted = escaped || b != b'"';
+            escaped = !escaped && b == b'\\';
+            false
+        } else {
+            quoted = b == b'"';
+            b == b',' || b == b'\r'
+        }
+    })
+}
+
+/// Index after an optional line ending at `index`.
+fn skip_line_ending(buf: &[u8], index: usize) -> usize {
+    match &buf[index..] {
+        [b'\r', b'\n', ..] => index + 2,
+        [b'\n', ..] => index + 1,
+        _ => index,
+    }
+}
+
+fn starts_with_ignore_case(buf: &[u8], prefix: &[u8]) -> bool {
+    buf.len() >= prefix.len()
+        && buf[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atat::InternalError;
+
+    /// Digest `input` completely, collecting the results.
+    fn digest_all(input: &[u8]) -> std::vec::Vec<DigestResult<'_>> {
+        let mut digester = CalypsoDigester::new();
+        let mut results = std::vec::Vec::new();
+        let mut position = 0;
+
+        while position < input.len() {
+            let (result, len) = digester.digest(&input[position..]);
+            if len == 0 {
+                break;
+            }
+            if result != DigestResult::None {
+                results.push(result);
+            }
+            position += len;
+        }
+
+        results
+    }
+
+    #[test]
+    fn payload_with_ok() {
+        let input = b"\r\n+recv:1,0,6,\r\nOK\r\n\r\nOK\r\n";
+
+        assert_eq!(
+            digest_all(input),
+            [DigestResult::Response(Ok(b"+recv:1,0,6,\r\nOK\r\n"))]
+        );
+    }
+
+    #[test]
+    fn incomplete_frames() {
+        let mut digester = CalypsoDigester::new();
+
+        for input in [
+            &b"+recv:1,0"[..],
+            b"+recv:1,0,6,abc",
+            b"+recv:1,0,3,abc\r\nO",
+            b"+eventmqtt:recv,topic,0,0,0,0,4,ab",
+        ] {
+            assert_eq!(digester.digest(input), (DigestResult::None, 0));
+        }
+    }
+
+    #[test]
+    fn event_before_ok() {
+        let input =
+            b"\r\n+recv:1,0,3,abc\r\n+eventsocket:TXFAILED,1,-105\r\nOK\r\n";
+
+        assert_eq!(
+            digest_all(input),
+            [
+                DigestResult::Response(Ok(b"+recv:1,0,3,abc")),
+                DigestResult::Urc(b"+eventsocket:TXFAILED,1,-105"),
+            ]
+        );
+    }
+
+    #[test]
+    fn event_with_payload() {
+        let input = b"+eventmqtt:recv,a/b,0,0,0,0,4,\r\n\r\n\r\nOK\r\n";
+
+        assert_eq!(
+            digest_all(input),
+            [
+                DigestResult::Urc(b"+eventmqtt:recv,a/b,0,0,0,0,4,\r\n\r\n"),
+                DigestResult::Response(Ok(b"")),
+            ]
+        );
+    }
+
+    #[test]
+    fn event_with_quoted_topic() {
+        let input = b"+eventmqtt:recv,\"a,\\\"b\",0,0,0,0,2,ab\r\n";
+
+        assert_eq!(
+            digest_all(input),
+            [DigestResult::Urc(
+                b"+eventmqtt:recv,\"a,\\\"b\",0,0,0,0,2,ab"
+            )]
+        );
+    }
+
+    #[test]
+    fn error_with_code() {
+        let input = b"\r\nERROR:-2018\r\n";
+
+        assert_eq!(
+            digest_all(input),
+            [DigestResult::Response(Err(InternalError::Custom(b"-2018")))]
+        );
+    }
+}
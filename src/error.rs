@@ -0,0 +1,104 @@
+use crate::{ConnectError, CredentialError, TlsError};
+
+/// Driver errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Error reported by the AT client or the module.
+    At(atat::Error),
+    /// Error code reported by the module.
+    Module(i32),
+    /// TLS error reported by the module.
+    Tls(TlsError),
+    /// Payload does not fit into a single command.
+    PayloadTooLarge,
+    /// Argument exceeds the module's length limit.
+    ArgumentTooLong { length: usize, max: usize },
+    /// Index is not less than the number of slots on the module.
+    IndexOutOfRange { index: usize, max: usize },
+    /// Invalid WLAN credentials.
+    Credentials(CredentialError),
+    /// Connecting to a WLAN network failed.
+    Connect(ConnectError),
+    /// Unexpected HTTP status code.
+    HttpStatus(u16),
+    /// Transferred size does not match the expected size.
+    SizeMismatch { expected: u32, actual: u32 },
+    /// Checksum of the transferred data does not match the expected value.
+    CrcMismatch { expected: u32, actual: u32 },
+    /// The module did not respond in time.
+    Timeout,
+    /// A firmware update was rejected and the previous image restored.
+    UpdateRejected,
+    /// Reading from or writing to the host side of a transfer failed.
+    Io(embedded_io_async::ErrorKind),
+}
+
+impl From<CredentialError> for Error {
+    fn from(value: CredentialError) -> Self {
+        Self::Credentials(value)
+    }
+}
+
+impl From<ConnectError> for Error {
+    fn from(value: ConnectError) -> Self {
+        Self::Connect(value)
+    }
+}
+
+impl From<atat::Error> for Error {
+    fn from(value: atat::Error) -> Self {
+        if let atat::Error::CustomMessage(message) = &value {
+            if let Some(code) = error_code(message) {
+                return match TlsError::from_code(code) {
+                    Some(error) => Self::Tls(error),
+                    None => Self::Module(code),
+                };
+            }
+        }
+
+        Self::At(value)
+    }
+}
+
+/// Error code at the end of an error reason, e.g. `connect,-468`.
+fn error_code(reason: &[u8]) -> Option<i32> {
+    let reason = core::str::from_utf8(reason).ok()?;
+
+    reason.rsplit(',').next()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(message: &[u8]) -> atat::Error {
+        atat::Error::CustomMessage(message.try_into().unwrap())
+    }
+
+    #[test]
+    fn module_codes() {
+        assert_eq!(Error::from(custom(b"-2018")), Error::Module(-2018));
+        assert_eq!(Error::from(custom(b"socket, -11")), Error::Module(-11));
+    }
+
+    #[test]
+    fn tls_codes() {
+        assert_eq!(
+            Error::from(custom(b"connect,-468")),
+            Error::Tls(TlsError::UnknownCa)
+        );
+        assert_eq!(
+            Error::from(custom(b"-470")),
+            Error::Tls(TlsError::Other(-470))
+        );
+    }
+
+    #[test]
+    fn other_errors() {
+        assert_eq!(Error::from(custom(b"busy")), Error::At(custom(b"busy")));
+        assert_eq!(
+            Error::from(atat::Error::Timeout),
+            Error::At(atat::Error::Timeout)
+        );
+    }
+}
@@ -0,0 +1,512 @@
+use crate::command::file::{OpenFlags, ABORT_SIGNATURE, MAX_SIGNATURE_LENGTH};
+use crate::command::{check_length, DataFormat, Escaped};
+use crate::{
+    command, Calypso, Crc32, Error, Progress, Transfer, TransferOptions,
+    MAX_FILE_NAME_LENGTH, MAX_PAYLOAD_SIZE,
+};
+use atat::asynch::AtatClient;
+use atat::heapless_bytes::Bytes;
+use embedded_io_async::{Error as _, Read, Write};
+use heapless::String;
+
+/// Handle to an open file in the module file system.
+///
+/// Created with [`Calypso::file_open`]. The file has to be closed with
+/// [`File::close`] for written contents to be committed.
+pub struct File<'a, C: AtatClient> {
+    calypso: &'a mut Calypso<C>,
+    id: u32,
+    token: u32,
+}
+
+impl<'a, C: AtatClient> File<'a, C> {
+    pub(crate) async fn open(
+        calypso: &'a mut Calypso<C>,
+        name: &str,
+        flags: OpenFlags,
+        max_size: u32,
+    ) -> Result<Self, Error> {
+        let response = calypso
+            .client
+            .send(&command::file::Open {
+                name: Escaped::new(name, MAX_FILE_NAME_LENGTH.into())?,
+                options: flags.into(),
+                size: max_size,
+            })
+            .await?;
+
+        Ok(Self {
+            calypso,
+            id: response.id,
+            token: response.token,
+        })
+    }
+
+    /// File descriptor on the module.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Secure token of the file, zero for non-secure files.
+    pub fn token(&self) -> u32 {
+        self.token
+    }
+
+    /// Read from the file at `offset` into `buf`.
+    ///
+    /// At most [`MAX_PAYLOAD_SIZE`] bytes are read at once. Returns the number
+    /// of bytes read, which is zero at the end of the file.
+    pub async fn read(
+        &mut self,
+        offset: u32,
+        buf: &mut [u8],
+    ) -> Result<usize, Error> {
+        self.read_chunk(offset, buf, DataFormat::Binary).await
+    }
+
+    /// Write `data` to the file at `offset`.
+    ///
+    /// The data must not be longer than [`MAX_PAYLOAD_SIZE`]. Returns the
+    /// number of bytes written.
+    pub async fn write(
+        &mut self,
+        offset: u32,
+        data: &[u8],
+    ) -> Result<usize, Error> {
+        self.write_chunk(offset, data, DataFormat::Binary).await
+    }
+
+    async fn read_chunk(
+        &mut self,
+        offset: u32,
+        buf: &mut [u8],
+        format: DataFormat,
+    ) -> Result<usize, Error> {
+        let response = self
+            .calypso
+            .client
+            .send(&command::file::Read {
+                id: self.id,
+                offset,
+                format,
+                length: buf
+                    .len()
+                    .min(format.max_decoded(MAX_PAYLOAD_SIZE.into()))
+                    as u16,
+            })
+            .await?;
+
+        format.decode(&response.data, buf)
+    }
+
+    async fn write_chunk(
+        &mut self,
+        offset: u32,
+        data: &[u8],
+        format: DataFormat,
+    ) -> Result<usize, Error> {
+        let mut buf = Bytes::new();
+        let data = format.encode(data, &mut buf)?;
+
+        let response = self
+            .calypso
+            .client
+            .send(&command::file::Write {
+                id: self.id,
+                offset,
+                format,
+                length: data.len() as u16,
+                data,
+            })
+            .await?;
+
+        Ok(response.length.into())
+    }
+
+    /// Close the file.
+    pub async fn close(self) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::file::Close {
+                id: self.id,
+                certificate: Escaped::default(),
+                signature: String::new(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Close the file, discarding what was written since it was opened.
+    ///
+    /// A fail-safe file keeps its previous contents.
+    pub async fn abort(self) -> Result<(), Error> {
+        self.close_signed("", ABORT_SIGNATURE).await
+    }
+
+    /// Close a secure file, verifying its signature.
+    ///
+    /// The signature is checked against the certificate stored in the file
+    /// system under `certificate`.
+    pub async fn close_signed(
+        self,
+        certificate: &str,
+        signature: &str,
+    ) -> Result<(), Error> {
+        check_length(signature, MAX_SIGNATURE_LENGTH)?;
+
+        self.calypso
+            .client
+            .send(&command::file::Close {
+                id: self.id,
+                certificate: Escaped::new(
+                    certificate,
+                    MAX_FILE_NAME_LENGTH.into(),
+                )?,
+                signature: signature.into(),
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Number of file bytes transferred per command.
+fn chunk_size(options: &TransferOptions) -> usize {
+    let max = options.format.max_decoded(MAX_PAYLOAD_SIZE.into());
+
+    usize::from(options.chunk_size).clamp(1, max)
+}
+
+/// Upload the contents of `reader` into a file.
+pub(crate) async fn upload<C: AtatClient, R: Read>(
+    calypso: &mut Calypso<C>,
+    path: &str,
+    max_size: u32,
+    reader: &mut R,
+    options: &TransferOptions,
+    progress: &mut impl FnMut(Progress),
+) -> Result<Transfer, Error> {
+    let flags = OpenFlags::CREATE | OpenFlags::OVERWRITE | options.flags;
+    let mut file = File::open(calypso, path, flags, max_size).await?;
+    let token = file.token();
+
+    let result = upload_chunks(&mut file, max_size, reader, options, progress)
+        .await
+        .and_then(|transfer| check_crc(transfer, options));
+    let closed = match result {
+        Ok(_) => file.close().await,
+        Err(_) => file.abort().await,
+    };
+
+    let transfer = result?;
+    closed?;
+
+    // the file is committed by now, so a copy that does not match is deleted
+    let checked = check_stored(calypso, path, token, transfer, options).await;
+    if checked.is_err() {
+        calypso.file_delete(path, token).await.ok();
+    }
+
+    checked
+}
+
+/// Check the size and, if enabled, the contents of an uploaded file.
+async fn check_stored<C: AtatClient>(
+    calypso: &mut Calypso<C>,
+    path: &str,
+    token: u32,
+    transfer: Transfer,
+    options: &TransferOptions,
+) -> Result<Transfer, Error> {
+    let info = calypso.file_info(path, token).await?;
+    if info.size != transfer.size {
+        return Err(Error::SizeMismatch {
+            expected: transfer.size,
+            actual: info.size,
+        });
+    }
+
+    if options.verify {
+        let mut file = File::open(calypso, path, OpenFlags::READ, 0).await?;
+        let result = download_chunks(
+            &mut file,
+            transfer.size,
+            &mut Discard,
+            options,
+            &mut |_| {},
+        )
+        .await;
+        let closed = file.close().await;
+
+        let stored = result?;
+        closed?;
+
+        if stored.crc != transfer.crc {
+            return Err(Error::CrcMismatch {
+                expected: transfer.crc,
+                actual: stored.crc,
+            });
+        }
+    }
+
+    Ok(transfer)
+}
+
+async fn upload_chunks<C: AtatClient, R: Read>(
+    file: &mut File<'_, C>,
+    max_size: u32,
+    reader: &mut R,
+    options: &TransferOptions,
+    progress: &mut impl FnMut(Progress),
+) -> Result<Transfer, Error> {
+    let chunk_size = chunk_size(options);
+    let mut buf = [0; MAX_PAYLOAD_SIZE as usize];
+    let mut crc = Crc32::new();
+    let mut size = 0;
+
+    loop {
+        let mut filled = 0;
+        while filled < chunk_size {
+            let read = reader
+                .read(&mut buf[filled..chunk_size])
+                .await
+                .map_err(|e| Error::Io(e.kind()))?;
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+
+        if filled == 0 {
+            break;
+        }
+
+        if size + filled as u32 > max_size {
+            return Err(Error::PayloadTooLarge);
+        }
+
+        let mut chunk = &buf[..filled];
+        let mut retries = options.retries;
+        while !chunk.is_empty() {
+            let written =
+                match file.write_chunk(size, chunk, options.format).await {
+                    Ok(0) => {
+                        return Err(Error::SizeMismatch {
+                            expected: size + chunk.len() as u32,
+                            actual: size,
+                        })
+                    }
+                    Ok(written) => written.min(chunk.len()),
+                    Err(_) if retries > 0 => {
+                        retries -= 1;
+                        continue;
+                    }
+                    Err(error) => return Err(error),
+                };
+
+            crc.update(&chunk[..written]);
+            size += written as u32;
+            chunk = &chunk[written..];
+        }
+
+        progress(Progress {
+            transferred: size,
+            total: None,
+        });
+    }
+
+    Ok(Transfer {
+        size,
+        crc: crc.finish(),
+    })
+}
+
+/// Download the contents of a file into `writer`.
+pub(crate) async fn download<C: AtatClient, W: Write>(
+    calypso: &mut Calypso<C>,
+    path: &str,
+    token: u32,
+    writer: &mut W,
+    options: &TransferOptions,
+    progress: &mut impl FnMut(Progress),
+) -> Result<Transfer, Error> {
+    let info = calypso.file_info(path, token).await?;
+
+    let mut file = File::open(calypso, path, OpenFlags::READ, 0).await?;
+    let result =
+        download_chunks(&mut file, info.size, writer, options, progress).await;
+    let closed = file.close().await;
+
+    let transfer = result?;
+    closed?;
+
+    if transfer.size != info.size {
+        return Err(Error::SizeMismatch {
+            expected: info.size,
+            actual: transfer.size,
+        });
+    }
+
+    check_crc(transfer, options)
+}
+
+async fn download_chunks<C: AtatClient, W: Write>(
+    file: &mut File<'_, C>,
+    total: u32,
+    writer: &mut W,
+    options: &TransferOptions,
+    progress: &mut impl FnMut(Progress),
+) -> Result<Transfer, Error> {
+    let chunk_size = chunk_size(options);
+    let mut buf = [0; MAX_PAYLOAD_SIZE as usize];
+    let mut crc = Crc32::new();
+    let mut size = 0;
+    let mut retries = options.retries;
+
+    while size < total {
+        let length = chunk_size.min((total - size) as usize);
+        let read = match file
+            .read_chunk(size, &mut buf[..length], options.format)
+            .await
+        {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(_) if retries > 0 => {
+                retries -= 1;
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        retries = options.retries;
+
+        writer
+            .write_all(&buf[..read])
+            .await
+            .map_err(|e| Error::Io(e.kind()))?;
+        crc.update(&buf[..read]);
+        size += read as u32;
+
+        progress(Progress {
+            transferred: size,
+            total: Some(total),
+        });
+    }
+
+    writer.flush().await.map_err(|e| Error::Io(e.kind()))?;
+
+    Ok(Transfer {
+        size,
+        crc: crc.finish(),
+    })
+}
+
+fn check_crc(
+    transfer: Transfer,
+    options: &TransferOptions,
+) -> Result<Transfer, Error> {
+    match options.crc {
+        Some(crc) if crc != transfer.crc => Err(Error::CrcMismatch {
+            expected: crc,
+            actual: transfer.crc,
+        }),
+        _ => Ok(transfer),
+    }
+}
+
+/// Writer that throws away everything written to it.
+struct Discard;
+
+impl embedded_io_async::ErrorType for Discard {
+    type Error = core::convert::Infallible;
+}
+
+impl Write for Discard {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{block_on, MockClient};
+
+    #[test]
+    fn close_signed_rejects_long_signatures() {
+        let client = MockClient::default().respond(b"+fileopen:1,0");
+        let mut calypso = Calypso::new(client);
+        let signature = [b'a'; MAX_SIGNATURE_LENGTH + 1];
+        let signature = core::str::from_utf8(&signature).unwrap();
+
+        let result = block_on(async {
+            let file =
+                File::open(&mut calypso, "/a", OpenFlags::READ, 0).await?;
+            file.close_signed("/cert", signature).await
+        });
+
+        assert!(matches!(
+            result,
+            Err(Error::ArgumentTooLong {
+                length: 257,
+                max: MAX_SIGNATURE_LENGTH,
+            })
+        ));
+        assert_eq!(calypso.client.sent.len(), 1);
+    }
+
+    #[test]
+    fn failed_upload_is_aborted() {
+        let client = MockClient::default()
+            .respond(b"+fileopen:1,0")
+            .fail(atat::Error::Timeout)
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+        let options = TransferOptions {
+            retries: 0,
+            ..TransferOptions::default()
+        };
+
+        let result = block_on(upload(
+            &mut calypso,
+            "/a",
+            16,
+            &mut &b"abc"[..],
+            &options,
+            &mut |_| {},
+        ));
+
+        assert_eq!(result, Err(Error::At(atat::Error::Timeout)));
+        assert_eq!(calypso.client.sent[2], "AT+fileClose=1,,A\r\n");
+    }
+
+    #[test]
+    fn mismatched_upload_is_deleted() {
+        let client = MockClient::default()
+            .respond(b"+fileopen:1,0")
+            .respond(b"+filewrite:3")
+            .respond(b"")
+            .respond(b"+filegetinfo:0,2,4096,0,0,0,0,4096,1")
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+
+        let result = block_on(upload(
+            &mut calypso,
+            "/a",
+            16,
+            &mut &b"abc"[..],
+            &TransferOptions::default(),
+            &mut |_| {},
+        ));
+
+        assert_eq!(
+            result,
+            Err(Error::SizeMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(calypso.client.sent[2], "AT+fileClose=1,,\r\n");
+        assert_eq!(calypso.client.sent[4], "AT+fileDel=/a,0\r\n");
+    }
+}
@@ -0,0 +1,512 @@
+use crate::command::file::{OpenFlags, ABORT_SIGNATURE};
+use crate::command::http::{
+    Method, RequestHeader, ResponseHeader, MAX_HEADER_LENGTH, MAX_URI_LENGTH,
+};
+use crate::command::{DataFormat, Escaped, Payload};
+use crate::{
+    command, tls, Calypso, Crc32, Error, Progress, TlsFiles, Transfer,
+    MAX_FILE_NAME_LENGTH, MAX_HOST_NAME_LENGTH, MAX_PAYLOAD_SIZE,
+};
+use atat::asynch::AtatClient;
+use atat::heapless_bytes::Bytes;
+use core::fmt::Write;
+use heapless::String;
+
+/// Options for [`HttpClient::download`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadOptions {
+    /// Size the file is created with when the server sends no content length
+    pub max_size: u32,
+    /// Number of bytes read per chunk
+    ///
+    /// Each chunk has to fit into the ingress buffer of the AT client.
+    pub chunk_size: u16,
+    /// Number of times reading a chunk is retried by resuming the transfer
+    pub retries: u8,
+    /// Expected CRC-32 of the whole body
+    pub crc: Option<u32>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            max_size: 64 * 1024,
+            chunk_size: 512,
+            retries: 3,
+            crc: None,
+        }
+    }
+}
+
+/// Handle to one of the module's HTTP client instances.
+///
+/// Created with [`Calypso::http_create`].
+pub struct HttpClient<'a, C: AtatClient> {
+    calypso: &'a mut Calypso<C>,
+    index: u8,
+    body_pending: bool,
+    format: DataFormat,
+}
+
+impl<'a, C: AtatClient> HttpClient<'a, C> {
+    pub(crate) fn new(calypso: &'a mut Calypso<C>, index: u8) -> Self {
+        Self {
+            calypso,
+            index,
+            body_pending: false,
+            format: DataFormat::default(),
+        }
+    }
+
+    /// Index of the client instance on the module.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Encoding of request and response bodies on the wire.
+    pub fn format(&self) -> DataFormat {
+        self.format
+    }
+
+    /// Set the encoding of request and response bodies on the wire.
+    ///
+    /// Bodies are encoded and decoded by the driver.
+    pub fn set_format(&mut self, format: DataFormat) {
+        self.format = format;
+    }
+
+    /// Connect to a server.
+    ///
+    /// The host includes the scheme and optionally the port, e.g.
+    /// `https://example.com:8443`. TLS files are only used for `https`
+    /// connections.
+    pub async fn connect(
+        &mut self,
+        host: &str,
+        tls: Option<TlsFiles<'_>>,
+    ) -> Result<(), Error> {
+        let tls = tls.unwrap_or_default();
+
+        self.calypso
+            .client
+            .send(&command::http::Connect {
+                index: self.index,
+                host: Escaped::new(host, MAX_HOST_NAME_LENGTH.into())?,
+                flags: String::new(),
+                private_key: tls::file_name(tls.private_key)?,
+                certificate: tls::file_name(tls.certificate)?,
+                ca: tls::file_name(tls.ca)?,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Disconnect from the server.
+    pub async fn disconnect(&mut self) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::http::Disconnect { index: self.index })
+            .await?;
+        self.body_pending = false;
+
+        Ok(())
+    }
+
+    /// Set a request header.
+    ///
+    /// Persistent headers are sent with every following request, other
+    /// headers only with the next one.
+    pub async fn set_header(
+        &mut self,
+        field: RequestHeader,
+        value: &str,
+        persistent: bool,
+    ) -> Result<(), Error> {
+        if value.len() > MAX_HEADER_LENGTH {
+            return Err(Error::PayloadTooLarge);
+        }
+        let data = Payload(value.as_bytes());
+
+        self.calypso
+            .client
+            .send(&command::http::SetHeader {
+                index: self.index,
+                option: field.into(),
+                flags: String::from(if persistent {
+                    "PERSISTENT"
+                } else {
+                    "NOT_PERSISTENT"
+                }),
+                format: DataFormat::Binary,
+                length: data.len() as u16,
+                data,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Send a request and return the response status code.
+    ///
+    /// The body must not be longer than [`MAX_PAYLOAD_SIZE`] once encoded.
+    /// The response body can be read afterwards with
+    /// [`HttpClient::read_body`].
+    pub async fn request(
+        &mut self,
+        method: Method,
+        uri: &str,
+        body: &[u8],
+    ) -> Result<u16, Error> {
+        let mut buf = Bytes::new();
+        let data = self.format.encode(body, &mut buf)?;
+        let response = self
+            .calypso
+            .client
+            .send(&command::http::SendRequest {
+                index: self.index,
+                method: method.into(),
+                uri: Escaped::new(uri, MAX_URI_LENGTH)?,
+                flags: String::new(),
+                format: self.format,
+                length: data.len() as u16,
+                data,
+            })
+            .await?;
+        self.body_pending = true;
+
+        Ok(response.status)
+    }
+
+    /// Send a GET request.
+    pub async fn get(&mut self, uri: &str) -> Result<u16, Error> {
+        self.request(Method::Get, uri, &[]).await
+    }
+
+    /// Send a POST request.
+    pub async fn post(&mut self, uri: &str, body: &[u8]) -> Result<u16, Error> {
+        self.request(Method::Post, uri, body).await
+    }
+
+    /// Send a PUT request.
+    pub async fn put(&mut self, uri: &str, body: &[u8]) -> Result<u16, Error> {
+        self.request(Method::Put, uri, body).await
+    }
+
+    /// Send a DELETE request.
+    pub async fn delete(&mut self, uri: &str) -> Result<u16, Error> {
+        self.request(Method::Delete, uri, &[]).await
+    }
+
+    /// Read a header of the last response.
+    pub async fn header(
+        &mut self,
+        field: ResponseHeader,
+    ) -> Result<String<{ command::http::MAX_HEADER_LENGTH }>, Error> {
+        let header = self
+            .calypso
+            .client
+            .send(&command::http::GetHeader {
+                index: self.index,
+                option: field.into(),
+            })
+            .await?;
+
+        let value = core::str::from_utf8(&header.value)
+            .map_err(|_| atat::Error::Parse)?;
+
+        Ok(value.trim().into())
+    }
+
+    /// Content length of the last response, if the server sent one.
+    pub async fn content_length(&mut self) -> Result<Option<u32>, Error> {
+        let value = self.header(ResponseHeader::ContentLength).await?;
+
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        value
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::At(atat::Error::Parse))
+    }
+
+    /// Read the next chunk of the last response body into `buf`.
+    ///
+    /// Returns the number of bytes read, or zero once the whole body has been
+    /// read.
+    pub async fn read_body(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if !self.body_pending || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let length = buf
+            .len()
+            .min(self.format.max_decoded(MAX_PAYLOAD_SIZE.into()))
+            as u16;
+        let body = self
+            .calypso
+            .client
+            .send(&command::http::ReadBody {
+                index: self.index,
+                format: self.format,
+                length,
+            })
+            .await?;
+
+        let len = self.format.decode(&body.data, buf)?;
+        self.body_pending = body.more;
+
+        Ok(len)
+    }
+
+    /// Download a resource into a file in the module file system.
+    ///
+    /// The body is written to the file in chunks as it is received, so it
+    /// never has to be held in memory. If reading the body fails the transfer
+    /// is resumed with a `Range` request from the last byte written. Once
+    /// complete, the size is checked against the content length and the
+    /// checksum against [`DownloadOptions::crc`]. The file is written as a
+    /// fail-safe file, so a failed download keeps its previous contents.
+    pub async fn download(
+        &mut self,
+        uri: &str,
+        path: &str,
+        options: DownloadOptions,
+        mut progress: impl FnMut(Progress),
+    ) -> Result<Transfer, Error> {
+        let status = self.get(uri).await?;
+        if !(200..300).contains(&status) {
+            return Err(Error::HttpStatus(status));
+        }
+
+        let total = self.content_length().await?;
+
+        let flags =
+            OpenFlags::CREATE | OpenFlags::OVERWRITE | OpenFlags::FAILSAFE;
+        let file = self
+            .calypso
+            .client
+            .send(&command::file::Open {
+                name: Escaped::new(path, MAX_FILE_NAME_LENGTH.into())?,
+                options: flags.into(),
+                size: total.unwrap_or(options.max_size),
+            })
+            .await?;
+
+        let result = self
+            .download_body(uri, file.id, total, &options, &mut progress)
+            .await
+            .and_then(|download| check(download, total, options.crc));
+
+        // roll back the fail-safe file so it keeps its previous contents
+        let signature = if result.is_ok() { "" } else { ABORT_SIGNATURE };
+        let closed = self
+            .calypso
+            .client
+            .send(&command::file::Close {
+                id: file.id,
+                certificate: Escaped::default(),
+                signature: signature.into(),
+            })
+            .await;
+
+        let download = result?;
+        closed?;
+
+        Ok(download)
+    }
+
+    async fn download_body(
+        &mut self,
+        uri: &str,
+        file: u32,
+        total: Option<u32>,
+        options: &DownloadOptions,
+        progress: &mut impl FnMut(Progress),
+    ) -> Result<Transfer, Error> {
+        let chunk_size = options.chunk_size.clamp(1, MAX_PAYLOAD_SIZE);
+        let mut retries = options.retries;
+        let mut crc = Crc32::new();
+        let mut size = 0;
+
+        while self.body_pending && total.is_none_or(|total| size < total) {
+            let body = match self
+                .calypso
+                .client
+                .send(&command::http::ReadBody {
+                    index: self.index,
+                    format: DataFormat::Binary,
+                    length: chunk_size,
+                })
+                .await
+            {
+                Ok(body) => body,
+                Err(error) if retries > 0 => {
+                    retries -= 1;
+                    self.resume(uri, size).await.map_err(|_| error)?;
+                    continue;
+                }
+                Err(error) => return Err(error.into()),
+            };
+
+            retries = options.retries;
+
+            let mut data = &body.data[..];
+            while !data.is_empty() {
+                let written = self
+                    .calypso
+                    .client
+                    .send(&command::file::Write {
+                        id: file,
+                        offset: size,
+                        format: DataFormat::Binary,
+                        length: data.len() as u16,
+                        data: Payload(data),
+                    })
+                    .await?;
+
+                if written.length == 0 {
+                    return Err(Error::SizeMismatch {
+                        expected: size + data.len() as u32,
+                        actual: size,
+                    });
+                }
+
+                let written = usize::from(written.length).min(data.len());
+                crc.update(&data[..written]);
+                size += written as u32;
+                data = &data[written..];
+            }
+
+            self.body_pending = body.more;
+            progress(Progress {
+                transferred: size,
+                total,
+            });
+        }
+
+        self.body_pending = false;
+
+        Ok(Transfer {
+            size,
+            crc: crc.finish(),
+        })
+    }
+
+    /// Request the rest of a resource starting at `offset`.
+    async fn resume(&mut self, uri: &str, offset: u32) -> Result<(), Error> {
+        let mut range = String::<32>::new();
+        write!(range, "bytes={}-", offset).ok();
+        self.set_header(RequestHeader::Range, &range, false).await?;
+
+        match self.get(uri).await? {
+            206 => Ok(()),
+            status => Err(Error::HttpStatus(status)),
+        }
+    }
+
+    /// Destroy the client instance on the module.
+    pub async fn destroy(self) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::http::Destroy { index: self.index })
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Check a downloaded body against the content length and expected CRC.
+fn check(
+    download: Transfer,
+    total: Option<u32>,
+    crc: Option<u32>,
+) -> Result<Transfer, Error> {
+    if let Some(total) = total {
+        if download.size != total {
+            return Err(Error::SizeMismatch {
+                expected: total,
+                actual: download.size,
+            });
+        }
+    }
+
+    if let Some(crc) = crc {
+        if download.crc != crc {
+            return Err(Error::CrcMismatch {
+                expected: crc,
+                actual: download.crc,
+            });
+        }
+    }
+
+    Ok(download)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{block_on, MockClient};
+
+    #[test]
+    fn download_fails_when_nothing_is_written() {
+        let client = MockClient::default()
+            .respond(b"+httpsendreq:200")
+            .respond(b"+httpgetheader:0,1,3")
+            .respond(b"+fileopen:1,0")
+            .respond(b"+httpreadresbody:0,0,0,3,abc")
+            .respond(b"+filewrite:0")
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+
+        let result = block_on(HttpClient::new(&mut calypso, 0).download(
+            "/index.html",
+            "/index.html",
+            DownloadOptions::default(),
+            |_| {},
+        ));
+
+        assert_eq!(
+            result,
+            Err(Error::SizeMismatch {
+                expected: 3,
+                actual: 0
+            })
+        );
+        assert_eq!(calypso.client.sent[5], "AT+fileClose=1,,A\r\n");
+    }
+
+    #[test]
+    fn download_aborts_on_crc_mismatch() {
+        let client = MockClient::default()
+            .respond(b"+httpsendreq:200")
+            .respond(b"+httpgetheader:0,1,3")
+            .respond(b"+fileopen:1,0")
+            .respond(b"+httpreadresbody:0,0,0,3,abc")
+            .respond(b"+filewrite:3")
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+        let options = DownloadOptions {
+            crc: Some(0),
+            ..DownloadOptions::default()
+        };
+
+        let result = block_on(HttpClient::new(&mut calypso, 0).download(
+            "/index.html",
+            "/index.html",
+            options,
+            |_| {},
+        ));
+
+        assert_eq!(
+            result,
+            Err(Error::CrcMismatch {
+                expected: 0,
+                actual: 0x3524_41c2
+            })
+        );
+        assert_eq!(calypso.client.sent[5], "AT+fileClose=1,,A\r\n");
+    }
+}
@@ -1,17 +1,56 @@
 #![no_std]
 
+#[cfg(test)]
+extern crate std;
+
+pub mod certs;
 pub mod command;
 mod constants;
+mod crc;
+mod digester;
+mod error;
+mod file;
+pub mod http;
+#[cfg(test)]
+mod mock;
+pub mod mqtt;
+pub mod ota;
+mod socket;
+mod storage;
+mod tls;
+mod transfer;
+pub mod wlan;
 
 pub use atat;
 
-use atat::{asynch::AtatClient, Error};
-use command::wlan::SecurityEapType as WlanEnterpriseEapType;
+use atat::asynch::AtatClient;
+pub use certs::CertificateFile;
 use command::{
-    wlan::{Mode as WlanMode, SecurityType},
-    EmptyResponse,
+    device::{DateTime, Version},
+    file::{FileEntry, FileInfo, OpenFlags},
+    socket::{
+        SelectResponse, SocketFamily, SocketProtocol, SocketSet, SocketType,
+    },
+    wlan::{Connection, Mode as WlanMode},
+    EmptyResponse, Escaped,
 };
 pub use constants::*;
+pub use crc::Crc32;
+pub use digester::CalypsoDigester;
+pub use error::Error;
+pub use file::File;
+use heapless::Vec;
+pub use http::{DownloadOptions, HttpClient};
+pub use mqtt::{MqttClient, MqttConfig, MqttRouter};
+pub use ota::{OtaOptions, OtaStage};
+pub use socket::{Readiness, Socket, SocketMonitor};
+pub use storage::{blocks_needed, StorageInfo};
+pub use tls::{TlsError, TlsFiles, TlsOptions};
+pub use transfer::{Progress, Transfer, TransferOptions};
+pub use wlan::{
+    ConnectError, ConnectParams, CredentialError, Enterprise, EnterpriseTls,
+    Link, Security, WpsPin,
+};
 
 /// Calypso client
 pub struct Calypso<C: AtatClient> {
@@ -25,24 +64,24 @@ impl<C: AtatClient> Calypso<C> {
     }
 
     /// Start the network processor unit (NWP).
-    pub async fn start(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn start(&mut self) -> Result<EmptyResponse, atat::Error> {
         self.client.send(&command::device::Start {}).await
     }
 
     /// Stop the network processor unit (NWP).
-    pub async fn stop(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn stop(&mut self) -> Result<EmptyResponse, atat::Error> {
         self.client
             .send(&command::device::Stop { timeout: 0 })
             .await
     }
 
     /// Test the Calypso is responsive.
-    pub async fn test(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn test(&mut self) -> Result<EmptyResponse, atat::Error> {
         self.client.send(&command::device::Test {}).await
     }
 
     /// Reboot the Calypso.
-    pub async fn reboot(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn reboot(&mut self) -> Result<EmptyResponse, atat::Error> {
         self.client.send(&command::device::Reboot {}).await
     }
 
@@ -50,7 +89,9 @@ impl<C: AtatClient> Calypso<C> {
     ///
     /// Warning: Resetting of powering off the module during this operation can
     /// result in permanent damage to the module.
-    pub async fn factory_reset(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn factory_reset(
+        &mut self,
+    ) -> Result<EmptyResponse, atat::Error> {
         self.client.send(&command::device::FactoryReset {}).await
     }
 
@@ -60,7 +101,7 @@ impl<C: AtatClient> Calypso<C> {
     pub async fn sleep(
         &mut self,
         seconds: u32,
-    ) -> Result<EmptyResponse, Error> {
+    ) -> Result<EmptyResponse, atat::Error> {
         self.client
             .send(&command::device::Sleep {
                 timeout_secs: seconds,
@@ -69,234 +110,499 @@ impl<C: AtatClient> Calypso<C> {
     }
 
     /// Sleep until reset or interrupt.
-    pub async fn sleep_forever(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn sleep_forever(
+        &mut self,
+    ) -> Result<EmptyResponse, atat::Error> {
         self.client
             .send(&command::device::Sleep { timeout_secs: 0 })
             .await
     }
 
     /// Enter into power saving mode.
-    pub async fn powersave(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn powersave(&mut self) -> Result<EmptyResponse, atat::Error> {
         self.client.send(&command::device::PowerSave {}).await
     }
 
     /// Enter into provisioning mode.
-    pub async fn provisioning_start(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn provisioning_start(
+        &mut self,
+    ) -> Result<EmptyResponse, atat::Error> {
         self.client
             .send(&command::device::ProvisioningStart {})
             .await
     }
 
     /// Exit provisioning mode.
-    pub async fn provisioning_stop(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn provisioning_stop(
+        &mut self,
+    ) -> Result<EmptyResponse, atat::Error> {
         self.client
             .send(&command::device::ProvisioningStop {})
             .await
     }
 
-    /// Set WIFI operating mode.
-    pub async fn wlan_set_mode(
+    /// Read the firmware and hardware versions of the module.
+    pub async fn version_get(&mut self) -> Result<Version, Error> {
+        Ok(self.client.send(&command::device::GetVersion).await?)
+    }
+
+    /// Update the module firmware.
+    ///
+    /// The image of `size` bytes is written to [`OtaOptions::file`] and
+    /// installed by rebooting the module, waiting for its startup event on
+    /// `urcs`. If the module reports an unexpected version afterwards, or
+    /// the version it reported before the update, the previous image is
+    /// restored and [`Error::UpdateRejected`] returned.
+    /// Otherwise the new image is committed and its version returned.
+    ///
+    /// If the module does not start within [`OtaOptions::startup_timeout`],
+    /// [`Error::Timeout`] is returned and the image is left uncommitted; see
+    /// [`Calypso::ota_rollback`].
+    pub async fn ota_update<
+        R: embedded_io_async::Read,
+        const CAPACITY: usize,
+        const SUBSCRIBERS: usize,
+        const N: usize,
+    >(
         &mut self,
-        mode: WlanMode,
-    ) -> Result<EmptyResponse, Error> {
+        image: &mut R,
+        size: u32,
+        urcs: &mut atat::UrcSubscription<
+            '_,
+            command::Urc<N>,
+            CAPACITY,
+            SUBSCRIBERS,
+        >,
+        options: &OtaOptions<'_>,
+        mut progress: impl FnMut(OtaStage),
+    ) -> Result<Version, Error> {
+        ota::update(self, image, size, urcs, options, &mut progress).await
+    }
+
+    /// Accept the running firmware image after an update.
+    pub async fn ota_commit(&mut self) -> Result<(), Error> {
+        self.client.send(&command::ota::Commit {}).await?;
+
+        Ok(())
+    }
+
+    /// Restore the previous firmware image after an update.
+    ///
+    /// Takes effect after the next reboot.
+    pub async fn ota_rollback(&mut self) -> Result<(), Error> {
+        self.client.send(&command::ota::Rollback {}).await?;
+
+        Ok(())
+    }
+
+    /// Get the current time from the module clock.
+    pub async fn time_get(&mut self) -> Result<DateTime, Error> {
+        Ok(self.client.send(&command::device::GetTime {}).await?)
+    }
+
+    /// Set the module clock.
+    pub async fn time_set(&mut self, time: DateTime) -> Result<(), Error> {
         self.client
-            .send(&command::wlan::SetMode { mode: mode.into() })
-            .await
+            .send(&command::device::SetTime::from(time))
+            .await?;
+
+        Ok(())
     }
 
-    /// Scan for WIFI access points.
-    pub async fn wlan_scan(
+    /// Enable or disable the SNTP client.
+    pub async fn sntp_set_enabled(
         &mut self,
-        index: u8,
-        count: u8,
-    ) -> Result<EmptyResponse, Error> {
+        enable: bool,
+    ) -> Result<(), Error> {
         self.client
-            .send(&command::wlan::Scan { index, count })
-            .await
+            .send(&command::netapp::SntpEnable { enable })
+            .await?;
+
+        Ok(())
     }
 
-    /// Connect to a WIFI access point with "Open" security.
-    pub async fn wlan_connect_open(
+    /// Set one of the SNTP servers.
+    ///
+    /// The index must be less than [`MAX_SNTP_SERVERS`].
+    pub async fn sntp_set_server(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-    ) -> Result<EmptyResponse, Error> {
+        index: u8,
+        address: &str,
+    ) -> Result<(), Error> {
+        if index >= MAX_SNTP_SERVERS {
+            return Err(Error::IndexOutOfRange {
+                index: index.into(),
+                max: MAX_SNTP_SERVERS.into(),
+            });
+        }
+
         self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::Open.into(),
-                security_key: None,
-                security_ext_user: None,
-                security_ext_anon_user: None,
-                security_ext_eap_method: None,
+            .send(&command::netapp::SntpServer {
+                index,
+                address: Escaped::new(address, MAX_HOST_NAME_LENGTH.into())?,
             })
-            .await
+            .await?;
+
+        Ok(())
     }
 
-    /// Connect to a WIFI access point with WEP security.
-    pub async fn wlan_connect_wep(
+    /// Set the interval between automatic SNTP updates.
+    pub async fn sntp_set_update_interval(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-        password: &str,
-    ) -> Result<EmptyResponse, Error> {
+        seconds: u32,
+    ) -> Result<(), Error> {
         self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::Wep.into(),
-                security_key: Some(password.into()),
-                security_ext_user: None,
-                security_ext_anon_user: None,
-                security_ext_eap_method: None,
+            .send(&command::netapp::SntpUpdateInterval {
+                interval_secs: seconds,
             })
-            .await
+            .await?;
+
+        Ok(())
     }
 
-    /// Connect to a WIFI access point with WEP Shared security.
-    pub async fn wlan_connect_wep_shared(
+    /// Set the time zone as an offset from UTC in minutes.
+    pub async fn sntp_set_time_zone(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-        password: &str,
-    ) -> Result<EmptyResponse, Error> {
+        offset_mins: i16,
+    ) -> Result<(), Error> {
         self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::WepShared.into(),
-                security_key: Some(password.into()),
-                security_ext_user: None,
-                security_ext_anon_user: None,
-                security_ext_eap_method: None,
-            })
-            .await
+            .send(&command::netapp::SntpTimeZone { offset_mins })
+            .await?;
+
+        Ok(())
     }
 
-    /// Connect to a WIFI access point with WPA/WPA2 security.
-    pub async fn wlan_connect_wpa_wpa2(
+    /// Update the module clock from the SNTP servers now.
+    pub async fn sntp_update(&mut self) -> Result<(), Error> {
+        self.client.send(&command::netapp::UpdateTime {}).await?;
+
+        Ok(())
+    }
+
+    /// Create a HTTP client instance on the module.
+    pub async fn http_create(&mut self) -> Result<HttpClient<'_, C>, Error> {
+        let response = self.client.send(&command::http::Create {}).await?;
+
+        Ok(HttpClient::new(self, response.index))
+    }
+
+    /// Create a MQTT client instance on the module.
+    pub async fn mqtt_create(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-        password: &str,
-    ) -> Result<EmptyResponse, Error> {
-        self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::WpaWpa2.into(),
-                security_key: Some(password.into()),
-                security_ext_user: None,
-                security_ext_anon_user: None,
-                security_ext_eap_method: None,
-            })
-            .await
+        config: &MqttConfig<'_>,
+    ) -> Result<MqttClient<'_, C>, Error> {
+        MqttClient::create(self, config).await
     }
 
-    /// Connect to a WIFI access point with WPA2-Plus security.
-    pub async fn wlan_connect_wpa2_plus(
+    /// Open a file in the module file system.
+    ///
+    /// The maximum size is only used when a file is created.
+    pub async fn file_open(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-        password: &str,
-    ) -> Result<EmptyResponse, Error> {
-        self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::Wpa2Plus.into(),
-                security_key: Some(password.into()),
-                security_ext_user: None,
-                security_ext_anon_user: None,
-                security_ext_eap_method: None,
-            })
-            .await
+        name: &str,
+        flags: OpenFlags,
+        max_size: u32,
+    ) -> Result<File<'_, C>, Error> {
+        File::open(self, name, flags, max_size).await
     }
 
-    /// Connect to a WIFI access point with WPA3 security.
-    pub async fn wlan_connect_wpa3(
+    /// Delete a file.
+    ///
+    /// The token is zero for non-secure files.
+    pub async fn file_delete(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-        password: &str,
-    ) -> Result<EmptyResponse, Error> {
+        name: &str,
+        token: u32,
+    ) -> Result<(), Error> {
         self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::Wpa3.into(),
-                security_key: Some(password.into()),
-                security_ext_user: None,
-                security_ext_anon_user: None,
-                security_ext_eap_method: None,
+            .send(&command::file::Delete {
+                name: Escaped::new(name, MAX_FILE_NAME_LENGTH.into())?,
+                token,
             })
-            .await
+            .await?;
+
+        Ok(())
     }
 
-    /// Connect to a WIFI access point with Enterprise security.
-    pub async fn wlan_connect_enterprise(
+    /// Get information about a file.
+    ///
+    /// The token is zero for non-secure files.
+    pub async fn file_info(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-        user: Option<&str>,
-        anon_user: Option<&str>,
-        password: &str,
-        eap_method: WlanEnterpriseEapType,
-    ) -> Result<EmptyResponse, Error> {
-        self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::WpaEnt.into(),
-                security_key: Some(password.into()),
-                security_ext_user: user.map(|inner| inner.into()),
-                security_ext_anon_user: anon_user.map(|inner| inner.into()),
-                security_ext_eap_method: Some(eap_method.into()),
+        name: &str,
+        token: u32,
+    ) -> Result<FileInfo, Error> {
+        Ok(self
+            .client
+            .send(&command::file::GetInfo {
+                name: Escaped::new(name, MAX_FILE_NAME_LENGTH.into())?,
+                token,
             })
-            .await
+            .await?)
+    }
+
+    /// List the stored files.
+    ///
+    /// Fails if there are more than `N` files.
+    pub async fn file_list<const N: usize>(
+        &mut self,
+    ) -> Result<Vec<FileEntry, N>, Error> {
+        let list = self.client.send(&command::file::GetFileList::<N>).await?;
+
+        Ok(list.entries)
     }
 
-    /// Connect to a WIFI access point with WPS push-button security.
-    pub async fn wlan_connect_wps_pbc(
+    /// Report the capacity and usage of the file system.
+    ///
+    /// `total_blocks` is the number of [`STORAGE_BLOCK_SIZE`] blocks of the
+    /// serial flash available to files. The module does not report its
+    /// capacity, so pass the known size or [`SERIAL_FLASH_BLOCKS`]. Used
+    /// blocks are summed over the allocated blocks of the listed files, which
+    /// leaves out metadata and system files, so the free space is an upper
+    /// bound. Fails if there are more than `N` files.
+    pub async fn storage_info<const N: usize>(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-    ) -> Result<EmptyResponse, Error> {
+        total_blocks: u32,
+    ) -> Result<StorageInfo<N>, Error> {
+        let files = self.file_list::<N>().await?;
+
+        Ok(StorageInfo::new(total_blocks, files))
+    }
+
+    /// Upload the contents of `reader` into a file.
+    ///
+    /// The file is created or overwritten with the given maximum size and
+    /// written in chunks of [`TransferOptions::chunk_size`], retrying failed
+    /// chunks. Once complete, the checksum is compared to
+    /// [`TransferOptions::crc`] and the stored size to the number of bytes
+    /// read. A failed upload is aborted, and a stored file that does not
+    /// match is deleted.
+    pub async fn file_upload<R: embedded_io_async::Read>(
+        &mut self,
+        path: &str,
+        max_size: u32,
+        reader: &mut R,
+        options: &TransferOptions,
+        mut progress: impl FnMut(Progress),
+    ) -> Result<Transfer, Error> {
+        file::upload(self, path, max_size, reader, options, &mut progress).await
+    }
+
+    /// Download the contents of a file into `writer`.
+    ///
+    /// The file is read in chunks of [`TransferOptions::chunk_size`], retrying
+    /// failed chunks. Once complete, the number of bytes read is compared to
+    /// the stored size and the checksum to [`TransferOptions::crc`]. The
+    /// token is zero for non-secure files.
+    pub async fn file_download<W: embedded_io_async::Write>(
+        &mut self,
+        path: &str,
+        token: u32,
+        writer: &mut W,
+        options: &TransferOptions,
+        mut progress: impl FnMut(Progress),
+    ) -> Result<Transfer, Error> {
+        file::download(self, path, token, writer, options, &mut progress).await
+    }
+
+    /// Store a certificate or key.
+    ///
+    /// An existing file is replaced atomically: the data is written to a
+    /// temporary file and verified first, then copied into a fail-safe file
+    /// under the final name.
+    pub async fn cert_store(
+        &mut self,
+        file: CertificateFile<'_>,
+        data: &[u8],
+    ) -> Result<Transfer, Error> {
+        certs::store(self, file, data).await
+    }
+
+    /// Delete a stored certificate or key.
+    pub async fn cert_delete(
+        &mut self,
+        file: CertificateFile<'_>,
+    ) -> Result<(), Error> {
+        self.file_delete(file.name(), 0).await
+    }
+
+    /// List the stored certificates and keys.
+    ///
+    /// Fails if there are more than `N` files in total.
+    pub async fn cert_list<const N: usize>(
+        &mut self,
+    ) -> Result<Vec<FileEntry, N>, Error> {
+        certs::list(self).await
+    }
+
+    /// Create a socket.
+    ///
+    /// Secure sockets use [`SocketProtocol::Sec`] and are configured with
+    /// [`Socket::set_tls`] before connecting.
+    pub async fn socket_open(
+        &mut self,
+        family: SocketFamily,
+        type_: SocketType,
+        protocol: SocketProtocol,
+    ) -> Result<Socket<'_, C>, Error> {
+        Socket::open(self, family, type_, protocol).await
+    }
+
+    /// Wait until sockets are ready for reading or writing.
+    ///
+    /// Returns the ready sockets, which are empty if `timeout` expired. The
+    /// timeout is limited to
+    /// [`MAX_SELECT_TIMEOUT_MS`](command::socket::MAX_SELECT_TIMEOUT_MS). See
+    /// [`SocketMonitor`] to wake async tasks instead.
+    pub async fn socket_select(
+        &mut self,
+        read: SocketSet,
+        write: SocketSet,
+        timeout: core::time::Duration,
+    ) -> Result<SelectResponse, Error> {
+        socket::select(self, read, write, timeout).await
+    }
+
+    /// Set WIFI operating mode.
+    pub async fn wlan_set_mode(
+        &mut self,
+        mode: WlanMode,
+    ) -> Result<EmptyResponse, atat::Error> {
         self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::WpsPbc.into(),
-                security_key: None,
-                security_ext_user: None,
-                security_ext_anon_user: None,
-                security_ext_eap_method: None,
-            })
+            .send(&command::wlan::SetMode { mode: mode.into() })
             .await
     }
 
-    /// Connect to a WIFI access point with WPS pin-code security.
-    pub async fn wlan_connect_wps_pin(
+    /// Scan for WIFI access points.
+    pub async fn wlan_scan(
         &mut self,
-        ssid: &str,
-        bssid: Option<&str>,
-    ) -> Result<EmptyResponse, Error> {
+        index: u8,
+        count: u8,
+    ) -> Result<EmptyResponse, atat::Error> {
         self.client
-            .send(&command::wlan::Connect {
-                ssid: ssid.into(),
-                bssid: bssid.map(|inner| inner.into()),
-                security_type: SecurityType::WpsPin.into(),
-                security_key: None,
-                security_ext_user: None,
-                security_ext_anon_user: None,
-                security_ext_eap_method: None,
-            })
+            .send(&command::wlan::Scan { index, count })
             .await
     }
 
+    /// Connect to a WIFI access point.
+    ///
+    /// For enterprise networks the server validation settings of
+    /// [`EnterpriseTls`] are applied first.
+    pub async fn wlan_connect(
+        &mut self,
+        params: &ConnectParams<'_>,
+    ) -> Result<(), Error> {
+        let command = params.command()?;
+        if let Security::Enterprise(enterprise) = params.security() {
+            wlan::configure_enterprise(self, &enterprise.tls).await?;
+        }
+        self.client.send(&command).await?;
+
+        Ok(())
+    }
+
+    /// Connect to a WIFI access point with WPS and wait for the result.
+    ///
+    /// With a `pin` the PIN has to be entered at the access point, see
+    /// [`WpsPin`], otherwise its WPS button has to be pressed, within
+    /// `timeout`. Returns the network the module connected to.
+    pub async fn wlan_connect_wps<
+        const CAPACITY: usize,
+        const SUBSCRIBERS: usize,
+        const N: usize,
+    >(
+        &mut self,
+        pin: Option<&str>,
+        urcs: &mut atat::UrcSubscription<
+            '_,
+            command::Urc<N>,
+            CAPACITY,
+            SUBSCRIBERS,
+        >,
+        timeout: embassy_time::Duration,
+    ) -> Result<Connection, Error> {
+        let security = match pin {
+            Some(pin) => Security::WpsPin(pin),
+            None => Security::WpsPbc,
+        };
+
+        while urcs.try_next_message_pure().is_some() {}
+        self.wlan_connect(&ConnectParams::new("", security)).await?;
+
+        wlan::wait_connected(urcs, timeout).await
+    }
+
+    /// Connect to a WIFI access point and wait until an IP address is
+    /// acquired.
+    ///
+    /// Returns the access point and the IP configuration. Failed attempts
+    /// are reported as [`Error::Connect`], no connection within `timeout`
+    /// as [`Error::Timeout`].
+    pub async fn wlan_connect_and_wait<
+        const CAPACITY: usize,
+        const SUBSCRIBERS: usize,
+        const N: usize,
+    >(
+        &mut self,
+        params: &ConnectParams<'_>,
+        urcs: &mut atat::UrcSubscription<
+            '_,
+            command::Urc<N>,
+            CAPACITY,
+            SUBSCRIBERS,
+        >,
+        timeout: embassy_time::Duration,
+    ) -> Result<Link, Error> {
+        while urcs.try_next_message_pure().is_some() {}
+        self.wlan_connect(params).await?;
+
+        wlan::wait_online(urcs, timeout).await
+    }
+
+    /// Store a profile the module connects to automatically.
+    ///
+    /// Profiles with a higher priority are preferred. Returns the index of
+    /// the stored profile.
+    pub async fn wlan_profile_add(
+        &mut self,
+        params: &ConnectParams<'_>,
+        priority: u8,
+    ) -> Result<u8, Error> {
+        let command = params.profile(priority)?;
+        if let Security::Enterprise(enterprise) = params.security() {
+            wlan::configure_enterprise(self, &enterprise.tls).await?;
+        }
+        let response = self.client.send(&command).await?;
+
+        Ok(response.index)
+    }
+
     /// Manually disconnect from an existing WIFI connection.
-    pub async fn wifi_disconnect(&mut self) -> Result<EmptyResponse, Error> {
+    pub async fn wifi_disconnect(
+        &mut self,
+    ) -> Result<EmptyResponse, atat::Error> {
         self.client.send(&command::wlan::Disconnect {}).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::{block_on, MockClient};
+
+    #[test]
+    fn sntp_server_index() {
+        let client = MockClient::default().respond(b"");
+        let mut calypso = Calypso::new(client);
+
+        block_on(calypso.sntp_set_server(2, "pool.ntp.org")).unwrap();
+        assert_eq!(
+            block_on(calypso.sntp_set_server(MAX_SNTP_SERVERS, "pool.ntp.org")),
+            Err(Error::IndexOutOfRange { index: 3, max: 3 })
+        );
+        assert_eq!(
+            calypso.client.sent,
+            ["AT+netAppSet=SNTP_CLIENT,SERVER,2,pool.ntp.org\r\n"]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Scripted AT client for unit tests.
+
+use atat::asynch::AtatClient;
+use atat::AtatCmd;
+use core::future::Future;
+use std::collections::VecDeque;
+use std::string::String;
+use std::vec::Vec;
+
+/// AT client answering commands with scripted responses.
+///
+/// Every command sent is recorded as a string. Responses are returned in the
+/// order they were scripted.
+#[derive(Default)]
+pub(crate) struct MockClient {
+    pub sent: Vec<String>,
+    responses: VecDeque<Result<Vec<u8>, atat::Error>>,
+}
+
+impl MockClient {
+    /// Answer the next command with `response`.
+    pub fn respond(mut self, response: &[u8]) -> Self {
+        self.responses.push_back(Ok(response.into()));
+        self
+    }
+
+    /// Fail the next command with `error`.
+    pub fn fail(mut self, error: atat::Error) -> Self {
+        self.responses.push_back(Err(error));
+        self
+    }
+}
+
+impl AtatClient for MockClient {
+    async fn send<Cmd: AtatCmd<LEN>, const LEN: usize>(
+        &mut self,
+        cmd: &Cmd,
+    ) -> Result<Cmd::Response, atat::Error> {
+        self.sent
+            .push(String::from_utf8_lossy(&cmd.as_bytes()).into_owned());
+
+        match self.responses.pop_front().expect("unexpected command") {
+            Ok(response) => cmd.parse(Ok(&response)),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Run a future to completion.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
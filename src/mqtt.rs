@@ -0,0 +1,516 @@
+use crate::command::mqtt::{
+    Event, Message, ProtocolVersion, QoS, SetOption, MAX_CLIENT_ID_LENGTH,
+    MAX_CREDENTIAL_LENGTH, MAX_TOPIC_LENGTH, MAX_WILL_MESSAGE_LENGTH,
+};
+use crate::command::{DataFormat, Escaped, Payload, Urc};
+use crate::{command, tls, Calypso, Error, TlsFiles, MAX_HOST_NAME_LENGTH};
+use atat::asynch::AtatClient;
+use atat::heapless_bytes::Bytes;
+use atat::UrcSubscription;
+use core::cell::RefCell;
+use core::fmt::Write;
+use core::net::IpAddr;
+use embassy_sync::blocking_mutex::{raw::RawMutex, Mutex};
+use embassy_sync::channel::Channel;
+use heapless::String;
+
+/// TLS settings of a MQTT connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MqttTls<'a> {
+    /// Certificate and key files
+    pub files: TlsFiles<'a>,
+    /// Do not check the broker host name against its certificate
+    pub skip_domain_verify: bool,
+    /// Do not verify the broker certificate
+    pub skip_cert_verify: bool,
+    /// Do not check the validity period of the broker certificate
+    pub skip_date_verify: bool,
+}
+
+/// Configuration used to create a MQTT client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MqttConfig<'a> {
+    /// Client identifier
+    pub client_id: &'a str,
+    /// Broker IP address or host name
+    pub broker: &'a str,
+    /// Broker port
+    pub port: u16,
+    /// TLS settings, `None` for an unencrypted connection
+    pub tls: Option<MqttTls<'a>>,
+    /// Protocol version
+    pub protocol: ProtocolVersion,
+    /// Wait for messages to be transmitted before publish returns
+    pub blocking_send: bool,
+    /// Encoding of published and received messages on the wire
+    ///
+    /// Messages are encoded and decoded by the driver.
+    pub format: DataFormat,
+}
+
+/// Last will message published by the broker if the client disappears.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastWill<'a> {
+    /// Topic name
+    pub topic: &'a str,
+    /// Message
+    pub message: &'a [u8],
+    /// Quality of service
+    pub qos: QoS,
+    /// Retain the message on the broker
+    pub retain: bool,
+}
+
+/// Options used when connecting to the broker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectOptions<'a> {
+    /// Keep-alive interval in seconds
+    pub keep_alive_secs: u16,
+    /// Start a clean session
+    pub clean_session: bool,
+    /// User name
+    pub user: Option<&'a str>,
+    /// Password
+    pub password: Option<&'a str>,
+    /// Last will message
+    pub will: Option<LastWill<'a>>,
+}
+
+impl Default for ConnectOptions<'_> {
+    fn default() -> Self {
+        Self {
+            keep_alive_secs: 60,
+            clean_session: true,
+            user: None,
+            password: None,
+            will: None,
+        }
+    }
+}
+
+/// Handle to one of the module's MQTT client instances.
+///
+/// Created with [`Calypso::mqtt_create`].
+pub struct MqttClient<'a, C: AtatClient> {
+    calypso: &'a mut Calypso<C>,
+    index: u8,
+    format: DataFormat,
+}
+
+impl<'a, C: AtatClient> MqttClient<'a, C> {
+    pub(crate) async fn create(
+        calypso: &'a mut Calypso<C>,
+        config: &MqttConfig<'_>,
+    ) -> Result<Self, Error> {
+        let mut flags = String::<96>::new();
+        let mut flag = |name: &str| {
+            if !flags.is_empty() {
+                flags.push('|').ok();
+            }
+            flags.push_str(name).ok();
+        };
+
+        flag(match config.broker.parse::<IpAddr>() {
+            Ok(IpAddr::V4(_)) => "IP4",
+            Ok(IpAddr::V6(_)) => "IP6",
+            Err(_) => "URL",
+        });
+
+        let tls = config.tls.unwrap_or_default();
+        if config.tls.is_some() {
+            flag("SEC");
+        }
+        if tls.skip_domain_verify {
+            flag("SKIP_DOMAIN_VERIFY");
+        }
+        if tls.skip_cert_verify {
+            flag("SKIP_CERT_VERIFY");
+        }
+        if tls.skip_date_verify {
+            flag("SKIP_DATE_VERIFY");
+        }
+
+        let response = calypso
+            .client
+            .send(&command::mqtt::Create {
+                client_id: Escaped::new(
+                    config.client_id,
+                    MAX_CLIENT_ID_LENGTH,
+                )?,
+                flags,
+                address: Escaped::new(
+                    config.broker,
+                    MAX_HOST_NAME_LENGTH.into(),
+                )?,
+                port: config.port,
+                private_key: tls::file_name(tls.files.private_key)?,
+                certificate: tls::file_name(tls.files.certificate)?,
+                ca: tls::file_name(tls.files.ca)?,
+                protocol: config.protocol.into(),
+                blocking_send: config.blocking_send.into(),
+                format: config.format,
+            })
+            .await?;
+
+        Ok(Self {
+            calypso,
+            index: response.index,
+            format: config.format,
+        })
+    }
+
+    /// Index of the client instance on the module.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    async fn set(
+        &mut self,
+        option: SetOption,
+        value: &str,
+    ) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::mqtt::Set {
+                index: self.index,
+                option: option.into(),
+                value: Escaped::new(value, MAX_CREDENTIAL_LENGTH)?,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Connect to the broker.
+    pub async fn connect(
+        &mut self,
+        options: &ConnectOptions<'_>,
+    ) -> Result<(), Error> {
+        let mut keep_alive = String::<5>::new();
+        write!(keep_alive, "{}", options.keep_alive_secs).ok();
+        self.set(SetOption::KeepAlive, &keep_alive).await?;
+
+        self.set(
+            SetOption::Clean,
+            if options.clean_session { "1" } else { "0" },
+        )
+        .await?;
+
+        if let Some(user) = options.user {
+            self.set(SetOption::User, user).await?;
+        }
+
+        if let Some(password) = options.password {
+            self.set(SetOption::Password, password).await?;
+        }
+
+        if let Some(will) = options.will {
+            if will.message.len() > MAX_WILL_MESSAGE_LENGTH {
+                return Err(Error::PayloadTooLarge);
+            }
+
+            self.calypso
+                .client
+                .send(&command::mqtt::SetWill {
+                    index: self.index,
+                    option: SetOption::Will.into(),
+                    topic: Escaped::new(will.topic, MAX_TOPIC_LENGTH)?,
+                    qos: will.qos.into(),
+                    retain: will.retain.into(),
+                    length: will.message.len() as u16,
+                    message: Payload(will.message),
+                })
+                .await?;
+        }
+
+        self.calypso
+            .client
+            .send(&command::mqtt::Connect { index: self.index })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Disconnect from the broker.
+    pub async fn disconnect(&mut self) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::mqtt::Disconnect { index: self.index })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Publish a message.
+    ///
+    /// The message must not be longer than
+    /// [`MAX_PAYLOAD_SIZE`](crate::MAX_PAYLOAD_SIZE) once encoded.
+    pub async fn publish(
+        &mut self,
+        topic: &str,
+        message: &[u8],
+        qos: QoS,
+        retain: bool,
+    ) -> Result<(), Error> {
+        let mut buf = Bytes::new();
+        let message = self.format.encode(message, &mut buf)?;
+        self.calypso
+            .client
+            .send(&command::mqtt::Publish {
+                index: self.index,
+                topic: Escaped::new(topic, MAX_TOPIC_LENGTH)?,
+                qos: qos.into(),
+                retain: retain.into(),
+                length: message.len() as u16,
+                message,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Subscribe to a topic filter.
+    pub async fn subscribe(
+        &mut self,
+        topic: &str,
+        qos: QoS,
+    ) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::mqtt::Subscribe {
+                index: self.index,
+                count: 1,
+                topic: Escaped::new(topic, MAX_TOPIC_LENGTH)?,
+                qos: qos.into(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Unsubscribe from a topic filter.
+    pub async fn unsubscribe(&mut self, topic: &str) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::mqtt::Unsubscribe {
+                index: self.index,
+                count: 1,
+                topic: Escaped::new(topic, MAX_TOPIC_LENGTH)?,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete the client instance on the module.
+    pub async fn delete(self) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::mqtt::Delete { index: self.index })
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Returns true if a topic name matches a topic filter.
+///
+/// Filters may contain the single level wildcard `+` and the multi level
+/// wildcard `#` as the last level.
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut filter = filter.split('/');
+    let mut topic = topic.split('/');
+
+    // wildcards do not match topics starting with `$`
+    if topic
+        .clone()
+        .next()
+        .is_some_and(|level| level.starts_with('$'))
+        && filter
+            .clone()
+            .next()
+            .is_some_and(|level| level == "+" || level == "#")
+    {
+        return false;
+    }
+
+    loop {
+        match (filter.next(), topic.next()) {
+            (Some("#"), _) => return filter.next().is_none(),
+            (Some("+"), Some(_)) => {}
+            (Some(f), Some(t)) if f == t => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Routes received MQTT messages to the tasks waiting on matching topics.
+///
+/// Every route has a queue of `DEPTH` messages with payloads of up to `N`
+/// bytes, matching the URCs the router is run with. Messages for a full
+/// queue are dropped.
+pub struct MqttRouter<
+    M: RawMutex,
+    const ROUTES: usize,
+    const DEPTH: usize,
+    const N: usize,
+> {
+    filters: Mutex<M, RefCell<[Option<String<MAX_TOPIC_LENGTH>>; ROUTES]>>,
+    channels: [Channel<M, Message<N>, DEPTH>; ROUTES],
+}
+
+impl<M: RawMutex, const ROUTES: usize, const DEPTH: usize, const N: usize>
+    MqttRouter<M, ROUTES, DEPTH, N>
+{
+    /// Creates a router without any routes.
+    pub const fn new() -> Self {
+        Self {
+            filters: Mutex::new(RefCell::new([const { None }; ROUTES])),
+            channels: [const { Channel::new() }; ROUTES],
+        }
+    }
+
+    /// Register a topic filter.
+    ///
+    /// Returns `None` if all routes are in use or the filter is longer than
+    /// [`MAX_TOPIC_LENGTH`]. The route is released when the returned handle
+    /// is dropped.
+    pub fn route(
+        &self,
+        filter: &str,
+    ) -> Option<Route<'_, M, ROUTES, DEPTH, N>> {
+        let filter = filter.parse().ok()?;
+
+        self.filters.lock(|filters| {
+            let mut filters = filters.borrow_mut();
+            let index = filters.iter().position(Option::is_none)?;
+            filters[index] = Some(filter);
+
+            // discard anything left over from a previous route
+            while self.channels[index].try_receive().is_ok() {}
+
+            Some(Route {
+                router: self,
+                index,
+            })
+        })
+    }
+
+    /// Deliver a message to every route with a matching filter.
+    ///
+    /// Returns the number of routes the message was delivered to.
+    pub fn dispatch(&self, message: &Message<N>) -> usize {
+        self.filters.lock(|filters| {
+            filters
+                .borrow()
+                .iter()
+                .zip(self.channels.iter())
+                .filter(|(filter, _)| {
+                    filter.as_ref().is_some_and(|filter| {
+                        topic_matches(filter, &message.topic)
+                    })
+                })
+                .filter(|(_, channel)| {
+                    channel.try_send(message.clone()).is_ok()
+                })
+                .count()
+        })
+    }
+
+    /// Dispatch messages from an URC subscription forever.
+    pub async fn run<const CAPACITY: usize, const SUBSCRIBERS: usize>(
+        &self,
+        urcs: &mut UrcSubscription<'_, Urc<N>, CAPACITY, SUBSCRIBERS>,
+    ) -> ! {
+        loop {
+            if let Urc::Mqtt(Event::Recv(message)) =
+                urcs.next_message_pure().await
+            {
+                self.dispatch(&message);
+            }
+        }
+    }
+}
+
+impl<M: RawMutex, const ROUTES: usize, const DEPTH: usize, const N: usize>
+    Default for MqttRouter<M, ROUTES, DEPTH, N>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A topic filter registered with a [`MqttRouter`].
+pub struct Route<
+    'a,
+    M: RawMutex,
+    const ROUTES: usize,
+    const DEPTH: usize,
+    const N: usize,
+> {
+    router: &'a MqttRouter<M, ROUTES, DEPTH, N>,
+    index: usize,
+}
+
+impl<M: RawMutex, const ROUTES: usize, const DEPTH: usize, const N: usize>
+    Route<'_, M, ROUTES, DEPTH, N>
+{
+    /// Wait for the next message matching the filter.
+    pub async fn recv(&self) -> Message<N> {
+        self.router.channels[self.index].receive().await
+    }
+
+    /// Next message matching the filter, if one is queued.
+    pub fn try_recv(&self) -> Option<Message<N>> {
+        self.router.channels[self.index].try_receive().ok()
+    }
+}
+
+impl<M: RawMutex, const ROUTES: usize, const DEPTH: usize, const N: usize> Drop
+    for Route<'_, M, ROUTES, DEPTH, N>
+{
+    fn drop(&mut self) {
+        self.router.filters.lock(|filters| {
+            filters.borrow_mut()[self.index] = None;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_topics() {
+        assert!(topic_matches("a/b", "a/b"));
+        assert!(!topic_matches("a/b", "a/c"));
+        assert!(!topic_matches("a/b", "a/b/c"));
+        assert!(!topic_matches("a/b/c", "a/b"));
+    }
+
+    #[test]
+    fn single_level_wildcard() {
+        assert!(topic_matches("a/+/c", "a/b/c"));
+        assert!(topic_matches("+/+", "a/b"));
+        assert!(topic_matches("a/+", "a/"));
+        assert!(!topic_matches("a/+", "a/b/c"));
+        assert!(!topic_matches("+", "a/b"));
+    }
+
+    #[test]
+    fn multi_level_wildcard() {
+        assert!(topic_matches("#", "a/b/c"));
+        assert!(topic_matches("a/#", "a/b/c"));
+        assert!(topic_matches("a/#", "a"));
+        assert!(!topic_matches("a/#/c", "a/b/c"));
+        assert!(!topic_matches("b/#", "a/b"));
+    }
+
+    #[test]
+    fn system_topics() {
+        assert!(!topic_matches("#", "$SYS/uptime"));
+        assert!(!topic_matches("+/uptime", "$SYS/uptime"));
+        assert!(topic_matches("$SYS/#", "$SYS/uptime"));
+    }
+}
@@ -0,0 +1,198 @@
+//! Over-the-air firmware update.
+
+use crate::command::device::Version;
+use crate::command::file::OpenFlags;
+use crate::command::{Escaped, Urc};
+use crate::{
+    command, Calypso, Error, Progress, TransferOptions, MAX_FILE_NAME_LENGTH,
+};
+use atat::asynch::AtatClient;
+use atat::UrcSubscription;
+use embassy_time::{with_timeout, Duration};
+use embedded_io_async::Read;
+
+/// Stage of a firmware update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtaStage {
+    /// Writing the image into the file system
+    Upload(Progress),
+    /// Rebooting into the new image
+    Restart,
+    /// Checking the version of the new image
+    Verify,
+    /// Accepting the new image
+    Commit,
+    /// Restoring the previous image
+    Rollback,
+}
+
+/// Options for a firmware update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OtaOptions<'a> {
+    /// File the update image is written to
+    pub file: &'a str,
+    /// Options used to upload the image
+    pub transfer: TransferOptions,
+    /// Time to wait for the module to start after rebooting
+    pub startup_timeout: Duration,
+    /// Firmware version expected after the update
+    ///
+    /// If the module reports a different version, the previous image is
+    /// restored. Without an expected version, the update is rolled back if
+    /// the version did not change.
+    pub expected_version: Option<&'a str>,
+}
+
+impl Default for OtaOptions<'_> {
+    fn default() -> Self {
+        Self {
+            file: "/sys/ota.bin",
+            transfer: TransferOptions {
+                verify: true,
+                ..TransferOptions::default()
+            },
+            startup_timeout: Duration::from_secs(60),
+            expected_version: None,
+        }
+    }
+}
+
+/// Update the module firmware with the image read from `image`.
+pub(crate) async fn update<
+    C: AtatClient,
+    R: Read,
+    const CAPACITY: usize,
+    const SUBSCRIBERS: usize,
+    const N: usize,
+>(
+    calypso: &mut Calypso<C>,
+    image: &mut R,
+    size: u32,
+    urcs: &mut UrcSubscription<'_, Urc<N>, CAPACITY, SUBSCRIBERS>,
+    options: &OtaOptions<'_>,
+    progress: &mut impl FnMut(OtaStage),
+) -> Result<Version, Error> {
+    let previous = calypso.version_get().await?;
+
+    let transfer = TransferOptions {
+        flags: options.transfer.flags | OpenFlags::FAILSAFE,
+        ..options.transfer
+    };
+    calypso
+        .file_upload(options.file, size, image, &transfer, |p| {
+            progress(OtaStage::Upload(p))
+        })
+        .await?;
+
+    calypso
+        .client
+        .send(&command::ota::Start {
+            file: Escaped::new(options.file, MAX_FILE_NAME_LENGTH.into())?,
+        })
+        .await?;
+
+    progress(OtaStage::Restart);
+    restart(calypso, urcs, options.startup_timeout).await?;
+
+    progress(OtaStage::Verify);
+    let version = calypso.version_get().await?;
+    let accepted = match options.expected_version {
+        Some(expected) => version.firmware == expected,
+        None => version.firmware != previous.firmware,
+    };
+    if !accepted {
+        progress(OtaStage::Rollback);
+        calypso.ota_rollback().await?;
+        restart(calypso, urcs, options.startup_timeout).await?;
+
+        return Err(Error::UpdateRejected);
+    }
+
+    progress(OtaStage::Commit);
+    calypso.ota_commit().await?;
+    calypso.file_delete(options.file, 0).await?;
+
+    Ok(version)
+}
+
+/// Reboot the module and wait for its startup event.
+async fn restart<
+    C: AtatClient,
+    const CAPACITY: usize,
+    const SUBSCRIBERS: usize,
+    const N: usize,
+>(
+    calypso: &mut Calypso<C>,
+    urcs: &mut UrcSubscription<'_, Urc<N>, CAPACITY, SUBSCRIBERS>,
+    timeout: Duration,
+) -> Result<(), Error> {
+    while urcs.try_next_message_pure().is_some() {}
+
+    calypso.reboot().await?;
+
+    with_timeout(timeout, async {
+        while urcs.next_message_pure().await != Urc::StartUp {}
+    })
+    .await
+    .map_err(|_| Error::Timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{block_on, MockClient};
+    use atat::{AtatUrcChannel, UrcChannel};
+
+    const VERSION: &[u8] =
+        b"+get:4,31.2.0.0.0,31.2.0.0.0,3.7.0.1,0.0.0.0,1.9.0";
+
+    #[test]
+    fn unchanged_version_is_rolled_back() {
+        let client = MockClient::default()
+            .respond(VERSION)
+            .respond(b"+fileopen:1,0")
+            .respond(b"+filewrite:3")
+            .respond(b"")
+            .respond(b"+filegetinfo:0,3,4096,0,0,0,0,4096,1")
+            .respond(b"")
+            .respond(b"")
+            .respond(VERSION)
+            .respond(b"")
+            .respond(b"");
+        let mut calypso = Calypso::new(client);
+        let channel = UrcChannel::<Urc, 4, 1>::new();
+        let mut urcs = channel.subscribe().unwrap();
+        let publisher = channel.publisher();
+        let options = OtaOptions {
+            transfer: TransferOptions::default(),
+            ..OtaOptions::default()
+        };
+        let mut stages = std::vec::Vec::new();
+        let mut record = |stage| stages.push(stage);
+        let mut image = &b"abc"[..];
+
+        let (result, _) = block_on(async {
+            tokio::join!(
+                update(
+                    &mut calypso,
+                    &mut image,
+                    3,
+                    &mut urcs,
+                    &options,
+                    &mut record,
+                ),
+                async {
+                    // each restart drains earlier events, so keep sending
+                    for _ in 0..4 {
+                        publisher.publish_immediate(Urc::StartUp);
+                        tokio::task::yield_now().await;
+                    }
+                }
+            )
+        });
+
+        assert_eq!(result, Err(Error::UpdateRejected));
+        assert_eq!(stages.last(), Some(&OtaStage::Rollback));
+        assert!(!stages.contains(&OtaStage::Commit));
+    }
+}
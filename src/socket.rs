@@ -0,0 +1,910 @@
+use crate::command::socket::{
+    Event, SelectResponse, SocketFamily, SocketOption, SocketProtocol,
+    SocketSet, SocketType, MAX_SELECT_TIMEOUT_MS,
+};
+use crate::command::{DataFormat, Escaped, Urc};
+use crate::{
+    command, Calypso, Error, TlsOptions, MAX_FILE_NAME_LENGTH,
+    MAX_HOST_NAME_LENGTH, MAX_IP_ADDRESS_LENGTH, MAX_PAYLOAD_SIZE,
+};
+use atat::asynch::AtatClient;
+use atat::heapless_bytes::Bytes;
+use atat::UrcSubscription;
+use core::cell::RefCell;
+use core::fmt::{Display, Write};
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use core::str::FromStr;
+use core::time::Duration;
+use embassy_sync::blocking_mutex::{raw::RawMutex, Mutex};
+use embassy_sync::signal::Signal;
+use heapless::String;
+
+/// Number of times a chunk is retried while the module is out of buffers.
+const SEND_RETRIES: u8 = 5;
+
+/// Delay before the first retry, doubled on every further retry.
+const SEND_BACKOFF: embassy_time::Duration =
+    embassy_time::Duration::from_millis(20);
+
+/// Module error code for temporarily unavailable resources.
+const EAGAIN: i32 = -11;
+
+/// Module error code for exhausted transmit buffers.
+const ENOBUFS: i32 = -105;
+
+/// Handle to a socket on the module.
+///
+/// Created with [`Calypso::socket_open`]. The socket has to be closed with
+/// [`Socket::close`] to free it on the module.
+pub struct Socket<'a, C: AtatClient> {
+    calypso: &'a mut Calypso<C>,
+    id: u8,
+    family: SocketFamily,
+    format: DataFormat,
+}
+
+impl<'a, C: AtatClient> Socket<'a, C> {
+    pub(crate) async fn open(
+        calypso: &'a mut Calypso<C>,
+        family: SocketFamily,
+        type_: SocketType,
+        protocol: SocketProtocol,
+    ) -> Result<Self, Error> {
+        let response = calypso
+            .client
+            .send(&command::socket::Socket {
+                family: family.into(),
+                type_: type_.into(),
+                protocol: protocol.into(),
+            })
+            .await?;
+
+        Ok(Self {
+            calypso,
+            id: response.id,
+            family,
+            format: DataFormat::default(),
+        })
+    }
+
+    /// Socket identifier on the module.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Encoding of sent and received data on the wire.
+    pub fn format(&self) -> DataFormat {
+        self.format
+    }
+
+    /// Set the encoding of sent and received data on the wire.
+    ///
+    /// Data is encoded and decoded by the driver, base64 reduces the amount
+    /// of data per command.
+    pub fn set_format(&mut self, format: DataFormat) {
+        self.format = format;
+    }
+
+    /// Largest number of bytes sent or received per command.
+    pub fn max_chunk_size(&self) -> usize {
+        self.format.max_decoded(MAX_PAYLOAD_SIZE.into())
+    }
+
+    /// Set a socket option.
+    pub(crate) async fn set_option(
+        &mut self,
+        option: SocketOption,
+        value: String<1024>,
+    ) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::socket::SetSockOpt {
+                socket_id: self.id,
+                level: option.level().into(),
+                option: option.into(),
+                value,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set a socket option to a formatted value.
+    async fn set_value(
+        &mut self,
+        option: SocketOption,
+        value: impl Display,
+    ) -> Result<(), Error> {
+        let mut formatted = String::new();
+        write!(formatted, "{}", value).map_err(|_| Error::PayloadTooLarge)?;
+
+        self.set_option(option, formatted).await
+    }
+
+    /// Get the value of a socket option.
+    async fn get_option(
+        &mut self,
+        option: SocketOption,
+    ) -> Result<String<64>, Error> {
+        let response = self
+            .calypso
+            .client
+            .send(&command::socket::GetSockOpt {
+                socket_id: self.id,
+                level: option.level().into(),
+                option: option.into(),
+            })
+            .await?;
+
+        Ok(response.value)
+    }
+
+    /// Get the value of a socket option, parsing up to `N` values separated
+    /// by commas.
+    async fn get_values<T: FromStr + Default + Copy, const N: usize>(
+        &mut self,
+        option: SocketOption,
+    ) -> Result<[T; N], Error> {
+        let value = self.get_option(option).await?;
+
+        let mut values = [T::default(); N];
+        let mut parts = value.split(',').map(str::trim);
+        for value in values.iter_mut() {
+            *value = parts
+                .next()
+                .and_then(|part| part.parse().ok())
+                .ok_or(Error::At(atat::Error::Parse))?;
+        }
+
+        Ok(values)
+    }
+
+    /// Enable or disable keep-alive messages.
+    pub async fn set_keep_alive(&mut self, enabled: bool) -> Result<(), Error> {
+        self.set_value(SocketOption::KeepAlive, u8::from(enabled))
+            .await
+    }
+
+    /// Returns true if keep-alive messages are enabled.
+    pub async fn keep_alive(&mut self) -> Result<bool, Error> {
+        let [enabled] =
+            self.get_values::<u8, 1>(SocketOption::KeepAlive).await?;
+
+        Ok(enabled != 0)
+    }
+
+    /// Set the keep-alive timeout in seconds.
+    pub async fn set_keep_alive_time(
+        &mut self,
+        secs: u32,
+    ) -> Result<(), Error> {
+        self.set_value(SocketOption::KeepAliveTime, secs).await
+    }
+
+    /// Keep-alive timeout in seconds.
+    pub async fn keep_alive_time(&mut self) -> Result<u32, Error> {
+        let [secs] = self.get_values(SocketOption::KeepAliveTime).await?;
+
+        Ok(secs)
+    }
+
+    /// Set the receive timeout of a blocking socket.
+    ///
+    /// `None` waits forever.
+    pub async fn set_recv_timeout(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let timeout = timeout.unwrap_or_default();
+
+        self.set_value(
+            SocketOption::RecvTimeout,
+            format_args!("{},{}", timeout.as_secs(), timeout.subsec_micros()),
+        )
+        .await
+    }
+
+    /// Receive timeout of a blocking socket, `None` if it waits forever.
+    pub async fn recv_timeout(&mut self) -> Result<Option<Duration>, Error> {
+        let [secs, micros] =
+            self.get_values::<u64, 2>(SocketOption::RecvTimeout).await?;
+        let timeout = Duration::from_secs(secs) + Duration::from_micros(micros);
+
+        Ok((!timeout.is_zero()).then_some(timeout))
+    }
+
+    /// Enable or disable non-blocking mode.
+    ///
+    /// In non-blocking mode, receiving and accepting return immediately
+    /// with an error if nothing is pending.
+    pub async fn set_nonblocking(
+        &mut self,
+        enabled: bool,
+    ) -> Result<(), Error> {
+        self.set_value(SocketOption::NonBlocking, u8::from(enabled))
+            .await
+    }
+
+    /// Returns true if the socket is in non-blocking mode.
+    pub async fn nonblocking(&mut self) -> Result<bool, Error> {
+        let [enabled] =
+            self.get_values::<u8, 1>(SocketOption::NonBlocking).await?;
+
+        Ok(enabled != 0)
+    }
+
+    /// Set the size of the receive buffer in bytes.
+    pub async fn set_recv_buffer_size(
+        &mut self,
+        size: u32,
+    ) -> Result<(), Error> {
+        self.set_value(SocketOption::RecvBuffer, size).await
+    }
+
+    /// Size of the receive buffer in bytes.
+    pub async fn recv_buffer_size(&mut self) -> Result<u32, Error> {
+        let [size] = self.get_values(SocketOption::RecvBuffer).await?;
+
+        Ok(size)
+    }
+
+    /// Allow or disallow sending broadcast datagrams.
+    pub async fn set_broadcast(&mut self, enabled: bool) -> Result<(), Error> {
+        self.set_value(SocketOption::Broadcast, u8::from(enabled))
+            .await
+    }
+
+    /// Returns true if sending broadcast datagrams is allowed.
+    pub async fn broadcast(&mut self) -> Result<bool, Error> {
+        let [enabled] =
+            self.get_values::<u8, 1>(SocketOption::Broadcast).await?;
+
+        Ok(enabled != 0)
+    }
+
+    /// Set how long closing waits for unsent data, in seconds.
+    ///
+    /// `None` returns from closing immediately.
+    pub async fn set_linger(&mut self, secs: Option<u16>) -> Result<(), Error> {
+        self.set_value(
+            SocketOption::Linger,
+            format_args!(
+                "{},{}",
+                u8::from(secs.is_some()),
+                secs.unwrap_or_default()
+            ),
+        )
+        .await
+    }
+
+    /// How long closing waits for unsent data, in seconds.
+    pub async fn linger(&mut self) -> Result<Option<u16>, Error> {
+        let [enabled, secs] =
+            self.get_values::<u16, 2>(SocketOption::Linger).await?;
+
+        Ok((enabled != 0).then_some(secs))
+    }
+
+    /// Join an IPv4 multicast group on the given local interface.
+    pub async fn join_multicast_v4(
+        &mut self,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), Error> {
+        self.set_value(
+            SocketOption::AddMembership,
+            format_args!("{},{}", group, interface),
+        )
+        .await
+    }
+
+    /// Leave an IPv4 multicast group on the given local interface.
+    pub async fn leave_multicast_v4(
+        &mut self,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), Error> {
+        self.set_value(
+            SocketOption::DropMembership,
+            format_args!("{},{}", group, interface),
+        )
+        .await
+    }
+
+    /// Join an IPv6 multicast group on the interface with the given index.
+    pub async fn join_multicast_v6(
+        &mut self,
+        group: Ipv6Addr,
+        interface: u32,
+    ) -> Result<(), Error> {
+        self.set_value(
+            SocketOption::AddMembershipV6,
+            format_args!("{},{}", group, interface),
+        )
+        .await
+    }
+
+    /// Leave an IPv6 multicast group on the interface with the given index.
+    pub async fn leave_multicast_v6(
+        &mut self,
+        group: Ipv6Addr,
+        interface: u32,
+    ) -> Result<(), Error> {
+        self.set_value(
+            SocketOption::DropMembershipV6,
+            format_args!("{},{}", group, interface),
+        )
+        .await
+    }
+
+    /// Set the time-to-live of sent IPv4 multicast datagrams.
+    pub async fn set_multicast_ttl_v4(&mut self, ttl: u8) -> Result<(), Error> {
+        self.set_value(SocketOption::MulticastTtl, ttl).await
+    }
+
+    /// Time-to-live of sent IPv4 multicast datagrams.
+    pub async fn multicast_ttl_v4(&mut self) -> Result<u8, Error> {
+        let [ttl] = self.get_values(SocketOption::MulticastTtl).await?;
+
+        Ok(ttl)
+    }
+
+    /// Configure TLS on a secure socket.
+    ///
+    /// Has to be called before [`Socket::connect`] or [`Socket::listen`].
+    pub async fn set_tls(
+        &mut self,
+        options: &TlsOptions<'_>,
+    ) -> Result<(), Error> {
+        let files = [
+            (SocketOption::SecureCaFile, options.files.ca),
+            (
+                SocketOption::SecureCertificateFile,
+                options.files.certificate,
+            ),
+            (
+                SocketOption::SecurePrivateKeyFile,
+                options.files.private_key,
+            ),
+        ];
+        for (option, file) in files {
+            if let Some(file) = file {
+                let name =
+                    Escaped::new(file.name(), MAX_FILE_NAME_LENGTH.into())?;
+                self.set_value(option, name).await?;
+            }
+        }
+
+        if let Some(domain) = options.domain {
+            let domain = Escaped::new(domain, MAX_HOST_NAME_LENGTH.into())?;
+            self.set_value(SocketOption::SecureDomainNameVerification, domain)
+                .await?;
+        }
+        if let Some(method) = options.method {
+            self.set_option(SocketOption::SecureMethod, method.into())
+                .await?;
+        }
+        if let Some(ciphers) = options.ciphers {
+            self.set_option(SocketOption::SecureMask, ciphers.into())
+                .await?;
+        }
+        if let Some(alpn) = options.alpn {
+            self.set_option(SocketOption::SecureAlpn, alpn.into())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Bind the socket to a local address.
+    pub async fn bind(&mut self, local: SocketAddr) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::socket::Bind {
+                socket_id: self.id,
+                family: family(&local).into(),
+                local_port: local.port(),
+                local_address: address(&local),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Listen for connections on a bound stream socket.
+    ///
+    /// Secure listeners present the certificate and private key set with
+    /// [`Socket::set_tls`]. If a CA file is set as well, clients have to
+    /// present a certificate signed by it.
+    pub async fn listen(&mut self, backlog: u8) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::socket::Listen {
+                socket_id: self.id,
+                backlog,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Accept a connection on a listening socket.
+    ///
+    /// Waits for a peer to connect and returns the connection together with
+    /// the peer address. On secure sockets this includes the TLS handshake,
+    /// failed handshakes are reported as [`Error::Tls`]. The listener can be
+    /// used again once the connection handle is dropped.
+    pub async fn accept(
+        &mut self,
+    ) -> Result<(Socket<'_, C>, SocketAddr), Error> {
+        let response = self
+            .calypso
+            .client
+            .send(&command::socket::Accept {
+                socket_id: self.id,
+                family: self.family.into(),
+            })
+            .await?;
+
+        let connection = Socket {
+            calypso: self.calypso,
+            id: response.socket_id,
+            family: self.family,
+            format: self.format,
+        };
+
+        Ok((connection, response.remote))
+    }
+
+    /// Connect to a remote address.
+    ///
+    /// On secure sockets, failed handshakes are reported as [`Error::Tls`].
+    pub async fn connect(&mut self, remote: SocketAddr) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::socket::Connect {
+                socket_id: self.id,
+                family: family(&remote).into(),
+                remote_port: remote.port(),
+                remote_address: address(&remote),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Send data on a connected socket.
+    ///
+    /// The data must not be longer than [`Socket::max_chunk_size`]. Returns
+    /// the number of bytes sent.
+    pub async fn send(&mut self, data: &[u8]) -> Result<usize, Error> {
+        let mut buf = Bytes::new();
+        let encoded = self.format.encode(data, &mut buf)?;
+        let response = self
+            .calypso
+            .client
+            .send(&command::socket::Send {
+                socket_id: self.id,
+                format: self.format,
+                length: encoded.len() as u16,
+                data: encoded,
+            })
+            .await?;
+
+        Ok(response.length.into())
+    }
+
+    /// Send data of any length on a connected socket.
+    ///
+    /// The data is split into chunks of [`Socket::max_chunk_size`] bytes and
+    /// bytes the module did not accept are sent again with the next chunk.
+    /// While the module is out of buffers, either rejecting a chunk or
+    /// reporting [`Event::TxFailed`] on `urcs`, sending backs off and
+    /// retries. Returns the total number of bytes sent.
+    ///
+    /// `urcs` has to be a subscription of its own, e.g. from
+    /// [`AtatUrcChannel::subscribe`](atat::AtatUrcChannel::subscribe), as
+    /// all events on it are consumed. Other subscriptions keep receiving
+    /// every event.
+    ///
+    /// The module does not report which bytes a failed transmission
+    /// contained, so data of a chunk that failed after being accepted is not
+    /// sent again.
+    pub async fn send_all<
+        const CAPACITY: usize,
+        const SUBSCRIBERS: usize,
+        const N: usize,
+    >(
+        &mut self,
+        data: &[u8],
+        mut urcs: UrcSubscription<'_, Urc<N>, CAPACITY, SUBSCRIBERS>,
+    ) -> Result<usize, Error> {
+        let mut sent = 0;
+        let mut retries = 0;
+
+        while sent < data.len() {
+            let mut failed = None;
+            while let Some(urc) = urcs.try_next_message_pure() {
+                if let Urc::Socket(Event::TxFailed { socket_id, error }) = urc {
+                    if socket_id == self.id {
+                        failed = Some(Error::Module(error));
+                    }
+                }
+            }
+
+            let end = data.len().min(sent + self.max_chunk_size());
+            let error = match failed {
+                Some(error) => error,
+                None => match self.send(&data[sent..end]).await {
+                    Ok(0) => Error::Module(ENOBUFS),
+                    Ok(length) => {
+                        sent += length;
+                        retries = 0;
+                        continue;
+                    }
+                    Err(Error::Module(code))
+                        if code == EAGAIN || code == ENOBUFS =>
+                    {
+                        Error::Module(code)
+                    }
+                    Err(error) => return Err(error),
+                },
+            };
+
+            if retries == SEND_RETRIES {
+                return Err(error);
+            }
+            embassy_time::Timer::after(SEND_BACKOFF * (1 << retries)).await;
+            retries += 1;
+        }
+
+        Ok(sent)
+    }
+
+    /// Receive data from a connected socket into `buf`.
+    ///
+    /// At most [`Socket::max_chunk_size`] bytes are received at once.
+    /// Returns the number of bytes received.
+    pub async fn recv(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let response = self
+            .calypso
+            .client
+            .send(&command::socket::Recv {
+                socket_id: self.id,
+                format: self.format,
+                length: buf.len().min(self.max_chunk_size()) as u16,
+            })
+            .await?;
+
+        self.format.decode(&response.data, buf)
+    }
+
+    /// Send a datagram to a remote address.
+    ///
+    /// Broadcast addresses require [`Socket::set_broadcast`]. The data must
+    /// not be longer than [`Socket::max_chunk_size`]. Returns the number of
+    /// bytes sent.
+    pub async fn send_to(
+        &mut self,
+        data: &[u8],
+        remote: SocketAddr,
+    ) -> Result<usize, Error> {
+        let mut buf = Bytes::new();
+        let encoded = self.format.encode(data, &mut buf)?;
+        let response = self
+            .calypso
+            .client
+            .send(&command::socket::SendTo {
+                socket_id: self.id,
+                family: family(&remote).into(),
+                remote_port: remote.port(),
+                remote_address: address(&remote),
+                format: self.format,
+                length: encoded.len() as u16,
+                data: encoded,
+            })
+            .await?;
+
+        Ok(response.length.into())
+    }
+
+    /// Receive a datagram into `buf`.
+    ///
+    /// Returns the number of bytes received and the sender address.
+    ///
+    /// Datagrams sent to joined multicast groups are received on the bound
+    /// port like any other.
+    ///
+    /// Reporting the destination group of a datagram is not supported: the
+    /// module's `+recvfrom` response only carries the sender address. Bind a
+    /// separate socket per group where the group matters.
+    pub async fn recv_from(
+        &mut self,
+        buf: &mut [u8],
+    ) -> Result<(usize, SocketAddr), Error> {
+        let response = self
+            .calypso
+            .client
+            .send(&command::socket::RecvFrom {
+                socket_id: self.id,
+                format: self.format,
+                length: buf.len().min(self.max_chunk_size()) as u16,
+            })
+            .await?;
+
+        let len = self.format.decode(&response.data, buf)?;
+
+        Ok((len, response.source))
+    }
+
+    /// Close the socket.
+    pub async fn close(self) -> Result<(), Error> {
+        self.calypso
+            .client
+            .send(&command::socket::Close { socket_id: self.id })
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Wait until sockets are ready for reading or writing.
+///
+/// Waits at most `timeout`, which is limited to [`MAX_SELECT_TIMEOUT_MS`].
+pub(crate) async fn select<C: AtatClient>(
+    calypso: &mut Calypso<C>,
+    read: SocketSet,
+    write: SocketSet,
+    timeout: Duration,
+) -> Result<SelectResponse, Error> {
+    let timeout =
+        timeout.min(Duration::from_millis(MAX_SELECT_TIMEOUT_MS.into()));
+
+    Ok(calypso
+        .client
+        .send(&command::socket::Select {
+            read,
+            write,
+            timeout_secs: timeout.as_secs() as u32,
+            timeout_usecs: timeout.subsec_micros(),
+        })
+        .await?)
+}
+
+/// Operations a socket is ready for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Readiness {
+    /// Data can be received or, on listeners, a connection accepted
+    pub readable: bool,
+    /// Data can be sent
+    pub writable: bool,
+}
+
+impl Readiness {
+    /// Ready for reading.
+    pub const READABLE: Self = Self {
+        readable: true,
+        writable: false,
+    };
+    /// Ready for writing.
+    pub const WRITABLE: Self = Self {
+        readable: false,
+        writable: true,
+    };
+
+    /// Returns true if any operation in `other` is ready.
+    pub const fn intersects(self, other: Self) -> bool {
+        (self.readable && other.readable) || (self.writable && other.writable)
+    }
+}
+
+/// Wakes the tasks waiting for sockets to become ready.
+///
+/// Tasks register their interest with [`SocketMonitor::wait`]. A single task
+/// owning the [`Calypso`] calls [`SocketMonitor::poll`] in a loop, which
+/// selects exactly the sockets someone is waiting for, and socket events are
+/// fed in with [`SocketMonitor::run`]. One task can wait on each socket at a
+/// time.
+pub struct SocketMonitor<M: RawMutex, const SOCKETS: usize> {
+    interests: Mutex<M, RefCell<[Readiness; SOCKETS]>>,
+    signals: [Signal<M, Readiness>; SOCKETS],
+}
+
+impl<M: RawMutex, const SOCKETS: usize> SocketMonitor<M, SOCKETS> {
+    /// Creates a monitor without any waiting tasks.
+    pub const fn new() -> Self {
+        Self {
+            interests: Mutex::new(RefCell::new(
+                [Readiness {
+                    readable: false,
+                    writable: false,
+                }; SOCKETS],
+            )),
+            signals: [const { Signal::new() }; SOCKETS],
+        }
+    }
+
+    /// Wait until a socket is ready for any operation in `interest`.
+    ///
+    /// Returns `None` if the socket identifier is not below `SOCKETS`.
+    pub async fn wait(
+        &self,
+        socket_id: u8,
+        interest: Readiness,
+    ) -> Option<Readiness> {
+        let index = usize::from(socket_id);
+        let signal = self.signals.get(index)?;
+
+        signal.reset();
+        self.set_interest(index, interest);
+        let _guard = InterestGuard {
+            monitor: self,
+            index,
+        };
+
+        loop {
+            let readiness = signal.wait().await;
+            if readiness.intersects(interest) {
+                return Some(readiness);
+            }
+        }
+    }
+
+    /// Sockets someone is waiting on, for reading and for writing.
+    pub fn interests(&self) -> (SocketSet, SocketSet) {
+        self.interests.lock(|interests| {
+            let mut read = SocketSet::new();
+            let mut write = SocketSet::new();
+            for (id, interest) in interests.borrow().iter().enumerate() {
+                if interest.readable {
+                    read.insert(id as u8);
+                }
+                if interest.writable {
+                    write.insert(id as u8);
+                }
+            }
+            (read, write)
+        })
+    }
+
+    /// Wake the task waiting on a socket.
+    pub fn notify(&self, socket_id: u8, readiness: Readiness) {
+        if let Some(signal) = self.signals.get(usize::from(socket_id)) {
+            signal.signal(readiness);
+        }
+    }
+
+    /// Wake the tasks waiting on the sockets of a select result.
+    pub fn notify_selected(&self, selected: SelectResponse) {
+        for id in 0..SocketSet::CAPACITY {
+            let readiness = Readiness {
+                readable: selected.read.contains(id),
+                writable: selected.write.contains(id),
+            };
+            if readiness != Readiness::default() {
+                self.notify(id, readiness);
+            }
+        }
+    }
+
+    /// Select the sockets someone is waiting on and wake the ready ones.
+    ///
+    /// Returns immediately if nobody is waiting.
+    pub async fn poll<C: AtatClient>(
+        &self,
+        calypso: &mut Calypso<C>,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let (read, write) = self.interests();
+        if read.is_empty() && write.is_empty() {
+            return Ok(());
+        }
+
+        let selected = select(calypso, read, write, timeout).await?;
+        self.notify_selected(selected);
+
+        Ok(())
+    }
+
+    /// Handle a socket event.
+    ///
+    /// Asynchronous notifications wake readers, so they observe closed or
+    /// failed connections on their next receive.
+    pub fn handle(&self, event: &Event) {
+        if let Event::AsyncEvent { socket_id, .. } = event {
+            self.notify(*socket_id, Readiness::READABLE);
+        }
+    }
+
+    /// Handle socket events from an URC subscription forever.
+    pub async fn run<
+        const CAPACITY: usize,
+        const SUBSCRIBERS: usize,
+        const N: usize,
+    >(
+        &self,
+        urcs: &mut UrcSubscription<'_, Urc<N>, CAPACITY, SUBSCRIBERS>,
+    ) -> ! {
+        loop {
+            if let Urc::Socket(event) = urcs.next_message_pure().await {
+                self.handle(&event);
+            }
+        }
+    }
+
+    fn set_interest(&self, index: usize, interest: Readiness) {
+        self.interests.lock(|interests| {
+            interests.borrow_mut()[index] = interest;
+        });
+    }
+}
+
+impl<M: RawMutex, const SOCKETS: usize> Default for SocketMonitor<M, SOCKETS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clears the interest of a waiting task, also when it is cancelled.
+struct InterestGuard<'a, M: RawMutex, const SOCKETS: usize> {
+    monitor: &'a SocketMonitor<M, SOCKETS>,
+    index: usize,
+}
+
+impl<M: RawMutex, const SOCKETS: usize> Drop for InterestGuard<'_, M, SOCKETS> {
+    fn drop(&mut self) {
+        self.monitor.set_interest(self.index, Readiness::default());
+    }
+}
+
+/// Address family of a socket address.
+fn family(address: &SocketAddr) -> SocketFamily {
+    match address {
+        SocketAddr::V4(_) => SocketFamily::Inet,
+        SocketAddr::V6(_) => SocketFamily::Inet6,
+    }
+}
+
+/// IP address of a socket address as sent to the module.
+fn address(address: &SocketAddr) -> String<{ MAX_IP_ADDRESS_LENGTH as usize }> {
+    let mut ip = String::new();
+    write!(ip, "{}", address.ip()).ok();
+    ip
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::wlan;
+    use crate::mock::{block_on, MockClient};
+    use atat::{AtatUrcChannel, UrcChannel};
+
+    #[test]
+    fn send_all_keeps_other_subscriptions() {
+        let channel = UrcChannel::<Urc, 4, 2>::new();
+        let mut other = channel.subscribe().unwrap();
+        let urcs = channel.subscribe().unwrap();
+        let publisher = channel.publisher();
+        publisher.publish_immediate(Urc::Wlan(wlan::Event::Other));
+        publisher.publish_immediate(Urc::Socket(Event::TxFailed {
+            socket_id: 1,
+            error: ENOBUFS,
+        }));
+
+        let client = MockClient::default().respond(b"+send:1,3");
+        let mut calypso = Calypso::new(client);
+        let mut socket = Socket {
+            calypso: &mut calypso,
+            id: 1,
+            family: SocketFamily::Inet,
+            format: DataFormat::Binary,
+        };
+
+        assert_eq!(block_on(socket.send_all(b"abc", urcs)), Ok(3));
+        assert_eq!(
+            other.try_next_message_pure(),
+            Some(Urc::Wlan(wlan::Event::Other))
+        );
+        assert!(other.try_next_message_pure().is_some());
+    }
+}
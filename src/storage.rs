@@ -0,0 +1,63 @@
+use crate::command::file::{FileEntry, FileFlags};
+use crate::STORAGE_BLOCK_SIZE;
+use heapless::Vec;
+
+/// Capacity and usage of the module file system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageInfo<const N: usize> {
+    /// Number of storage blocks available to files
+    pub total_blocks: u32,
+    /// Number of storage blocks allocated to files
+    pub used_blocks: u32,
+    /// Stored files
+    pub files: Vec<FileEntry, N>,
+}
+
+impl<const N: usize> StorageInfo<N> {
+    pub(crate) fn new(total_blocks: u32, files: Vec<FileEntry, N>) -> Self {
+        let used_blocks = files.iter().map(|file| file.allocated_blocks).sum();
+
+        Self {
+            total_blocks,
+            used_blocks,
+            files,
+        }
+    }
+
+    /// Number of unallocated storage blocks.
+    pub fn free_blocks(&self) -> u32 {
+        self.total_blocks.saturating_sub(self.used_blocks)
+    }
+
+    /// Number of unallocated bytes.
+    pub fn free_bytes(&self) -> u32 {
+        self.free_blocks().saturating_mul(STORAGE_BLOCK_SIZE)
+    }
+
+    /// Returns true if a new file of `size` bytes fits into the free blocks.
+    pub fn can_store(&self, size: u32, failsafe: bool) -> bool {
+        blocks_needed(size, failsafe) <= self.free_blocks()
+    }
+
+    /// Files with all of the given properties.
+    pub fn files_with(
+        &self,
+        flags: FileFlags,
+    ) -> impl Iterator<Item = &FileEntry> {
+        self.files
+            .iter()
+            .filter(move |file| file.flags.contains(flags))
+    }
+}
+
+/// Number of storage blocks allocated for a file of `size` bytes.
+///
+/// Fail-safe files keep a second copy and need twice the blocks.
+pub const fn blocks_needed(size: u32, failsafe: bool) -> u32 {
+    let blocks = size.div_ceil(STORAGE_BLOCK_SIZE);
+    if failsafe {
+        blocks * 2
+    } else {
+        blocks
+    }
+}
@@ -0,0 +1,93 @@
+use crate::command::socket::{Alpn, CipherSuites, TlsMethod};
+use crate::command::Escaped;
+use crate::{CertificateFile, Error, MAX_FILE_NAME_LENGTH};
+
+/// Files in the module file system used to secure a connection.
+///
+/// Unused files are left as `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TlsFiles<'a> {
+    /// Root CA certificate used to verify the peer
+    pub ca: Option<CertificateFile<'a>>,
+    /// Client certificate
+    pub certificate: Option<CertificateFile<'a>>,
+    /// Client private key
+    pub private_key: Option<CertificateFile<'a>>,
+}
+
+/// File name of an optional certificate, empty if unused.
+pub(crate) fn file_name<'a>(
+    file: Option<CertificateFile<'a>>,
+) -> Result<Escaped<'a>, Error> {
+    Escaped::new(
+        file.map(|file| file.name()).unwrap_or_default(),
+        MAX_FILE_NAME_LENGTH.into(),
+    )
+}
+
+/// Settings of a secure socket.
+///
+/// Unset options keep the module defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TlsOptions<'a> {
+    /// Certificate and key files
+    pub files: TlsFiles<'a>,
+    /// Domain name checked against the peer certificate and sent as SNI
+    pub domain: Option<&'a str>,
+    /// TLS protocol version
+    pub method: Option<TlsMethod>,
+    /// Allowed cipher suites
+    pub ciphers: Option<CipherSuites>,
+    /// Application protocols offered during the handshake
+    pub alpn: Option<Alpn>,
+}
+
+/// Errors reported by the module while establishing a TLS connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsError {
+    /// Peer certificate is not signed by a trusted CA
+    UnknownCa,
+    /// Peer certificate is not valid at the current date
+    DateError,
+    /// Peer certificate does not match the verified domain name
+    DomainMismatch,
+    /// Peer certificate has been revoked
+    CertificateRevoked,
+    /// Peer certificate is not the expected one
+    WrongPeerCertificate,
+    /// CA certificate file is missing
+    NoCaFile,
+    /// CA certificate file is invalid
+    BadCaFile,
+    /// Certificate file is invalid
+    BadCertificateFile,
+    /// Private key file is invalid
+    BadPrivateKeyFile,
+    /// Handshake did not complete in time
+    HandshakeTimeout,
+    /// Too many secure sockets are open
+    TooManySockets,
+    /// Any other TLS error code
+    Other(i32),
+}
+
+impl TlsError {
+    /// Classifies a module error code, if it is a TLS error.
+    pub const fn from_code(code: i32) -> Option<Self> {
+        Some(match code {
+            -468 | -188 => Self::UnknownCa,
+            -461 | -151 | -150 => Self::DateError,
+            -322 => Self::DomainMismatch,
+            -467 => Self::CertificateRevoked,
+            -469 => Self::WrongPeerCertificate,
+            -454 => Self::NoCaFile,
+            -456 => Self::BadCaFile,
+            -457 => Self::BadCertificateFile,
+            -458 => Self::BadPrivateKeyFile,
+            -462 => Self::HandshakeTimeout,
+            -460 => Self::TooManySockets,
+            -499..=-450 => Self::Other(code),
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use crate::command::file::OpenFlags;
+use crate::command::DataFormat;
+
+/// Progress of a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of bytes transferred so far
+    pub transferred: u32,
+    /// Total number of bytes, if known
+    pub total: Option<u32>,
+}
+
+/// A completed transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer {
+    /// Size of the file in bytes
+    pub size: u32,
+    /// CRC-32 of the file contents
+    pub crc: u32,
+}
+
+/// Options for file uploads and downloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferOptions {
+    /// Number of file bytes transferred per command
+    ///
+    /// Limited to what fits into a single command after encoding. Each
+    /// chunk also has to fit into the ingress buffer of the AT client.
+    pub chunk_size: u16,
+    /// Encoding of the data on the wire
+    pub format: DataFormat,
+    /// Number of times a failed chunk is retried
+    pub retries: u8,
+    /// Read an uploaded file back and compare its checksum
+    pub verify: bool,
+    /// Expected CRC-32 of the file contents
+    pub crc: Option<u32>,
+    /// Additional options used when creating a file, e.g.
+    /// [`OpenFlags::FAILSAFE`]
+    pub flags: OpenFlags,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 512,
+            format: DataFormat::Binary,
+            retries: 2,
+            verify: false,
+            crc: None,
+            flags: OpenFlags::default(),
+        }
+    }
+}
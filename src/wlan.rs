@@ -0,0 +1,803 @@
+use crate::command::netapp::{self, Ipv4Config};
+use crate::command::wlan::{
+    Connect, Connection, Event, GeneralOption, ProfileAdd, SecurityEapType,
+    SecurityType, SetGeneral, MAX_BSSID_LENGTH, MAX_SECURITY_KEY_LENGTH,
+    MAX_SSID_LENGTH, MAX_USER_LENGTH,
+};
+use crate::command::{Escaped, Urc};
+use crate::{Calypso, CertificateFile, Error};
+use atat::asynch::AtatClient;
+use atat::UrcSubscription;
+use core::fmt::Display;
+use embassy_time::{with_timeout, Duration};
+
+/// Maximum length of a WPA passphrase.
+const MAX_PASSPHRASE_LENGTH: usize = 63;
+
+/// Length of a WPA pre-shared key given as hex digits.
+const PSK_HEX_LENGTH: usize = 64;
+
+/// Invalid WLAN credentials, rejected before anything is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialError {
+    /// SSID is empty or longer than 32 bytes
+    SsidLength(usize),
+    /// WPA passphrase is not 8 to 63 characters long
+    PassphraseLength(usize),
+    /// 64 character WPA key is not a hex encoded pre-shared key
+    InvalidPsk,
+    /// WEP key is not 5 or 13 characters or 10 or 26 hex digits long
+    WepKeyLength(usize),
+    /// BSSID is not six colon separated hex octets
+    InvalidBssid,
+    /// Enterprise user name or password is too long
+    UserLength(usize),
+    /// WPS PIN is not 4 digits or 8 digits with a valid checksum
+    InvalidWpsPin,
+    /// Enterprise certificate is not in the file the module reads it from
+    CertificateFile,
+    /// Enterprise certificate file does not exist
+    MissingCertificate,
+    /// Character that cannot be sent in an AT argument, e.g. a line break
+    InvalidCharacter(char),
+}
+
+/// Reason a connection attempt failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectError {
+    /// Authentication failed, e.g. because of a wrong key
+    AuthFailed,
+    /// No access point of the network was found
+    ApNotFound,
+    /// Connected, but no IP address was acquired in time
+    DhcpTimeout,
+    /// Any other disconnect reason code
+    Disconnected(u16),
+}
+
+impl ConnectError {
+    /// Classifies the reason code of a disconnect event.
+    pub const fn from_reason(reason: u16) -> Self {
+        match reason {
+            // 802.11 reason codes: previous authentication invalid, MIC
+            // failure, 4-way handshake timeout and 802.1X failure
+            2 | 14 | 15 | 23 => Self::AuthFailed,
+            _ => Self::Disconnected(reason),
+        }
+    }
+
+    /// Classifies a disconnect event.
+    ///
+    /// Attempts that never reached an access point report no BSSID.
+    pub fn from_event(connection: &Connection, reason: u16) -> Self {
+        let unknown = connection
+            .bssid
+            .split(':')
+            .all(|octet| octet.trim_start_matches('0').is_empty());
+
+        match Self::from_reason(reason) {
+            Self::Disconnected(_) if unknown => Self::ApNotFound,
+            error => error,
+        }
+    }
+}
+
+/// WLAN connection with an IP address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Access point connected to
+    pub connection: Connection,
+    /// Acquired IP configuration
+    pub ip: Ipv4Config,
+}
+
+/// Eight digit WPS PIN ending in a checksum digit.
+///
+/// Shown to the user to be entered at the access point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WpsPin([u8; 8]);
+
+impl WpsPin {
+    /// PIN from the last seven digits of `value`, e.g. a random number.
+    pub const fn new(value: u32) -> Self {
+        let value = value % 10_000_000;
+        let mut pin = value * 10 + wps_checksum(value);
+
+        let mut digits = [b'0'; 8];
+        let mut i = digits.len();
+        while i > 0 {
+            i -= 1;
+            digits[i] = b'0' + (pin % 10) as u8;
+            pin /= 10;
+        }
+
+        Self(digits)
+    }
+
+    /// The PIN as a string of digits.
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.0).unwrap_or_default()
+    }
+}
+
+impl Display for WpsPin {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Checksum digit of a seven digit WPS PIN.
+const fn wps_checksum(mut value: u32) -> u32 {
+    let mut sum = 0;
+    let mut weight = 3;
+    while value > 0 {
+        sum += weight * (value % 10);
+        value /= 10;
+        weight = 4 - weight;
+    }
+
+    (10 - sum % 10) % 10
+}
+
+/// Certificates and server validation of an enterprise network.
+///
+/// The module reads enterprise certificates from fixed files. Store them
+/// once with [`Calypso::cert_store`] under [`CertificateFile::ENTERPRISE_CA`],
+/// [`CertificateFile::ENTERPRISE_CLIENT`] and
+/// [`CertificateFile::ENTERPRISE_PRIVATE_KEY`]; other files are rejected.
+/// Files that are set are checked to exist before connecting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnterpriseTls<'a> {
+    /// Root CA certificate the server is verified against
+    pub ca: Option<CertificateFile<'a>>,
+    /// Client certificate, e.g. for EAP-TLS
+    pub certificate: Option<CertificateFile<'a>>,
+    /// Client private key
+    pub private_key: Option<CertificateFile<'a>>,
+    /// Do not verify the server certificate against the CA
+    pub skip_ca_verify: bool,
+}
+
+impl EnterpriseTls<'_> {
+    fn validate(&self) -> Result<(), CredentialError> {
+        let files = [
+            (self.ca, CertificateFile::ENTERPRISE_CA),
+            (self.certificate, CertificateFile::ENTERPRISE_CLIENT),
+            (self.private_key, CertificateFile::ENTERPRISE_PRIVATE_KEY),
+        ];
+        for (file, expected) in files {
+            if file.is_some_and(|file| file.name() != expected.name()) {
+                return Err(CredentialError::CertificateFile);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Enterprise (802.1X) credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Enterprise<'a> {
+    /// EAP method
+    pub method: SecurityEapType,
+    /// User name
+    pub user: Option<&'a str>,
+    /// Anonymous user name sent outside the protected tunnel
+    pub anon_user: Option<&'a str>,
+    /// Password
+    pub password: &'a str,
+    /// Certificates and server validation
+    pub tls: EnterpriseTls<'a>,
+}
+
+/// Security of a WLAN network, with the credentials it needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Security<'a> {
+    /// No security
+    Open,
+    /// WEP with open authentication and the given key
+    Wep(&'a str),
+    /// WEP with shared key authentication and the given key
+    WepShared(&'a str),
+    /// WPA/WPA2 with the given passphrase or pre-shared key
+    WpaWpa2(&'a str),
+    /// WPA2-Plus with the given passphrase or pre-shared key
+    Wpa2Plus(&'a str),
+    /// WPA3 with the given passphrase
+    Wpa3(&'a str),
+    /// WPA enterprise
+    Enterprise(Enterprise<'a>),
+    /// WPS push-button
+    WpsPbc,
+    /// WPS with the given PIN, see [`WpsPin`]
+    WpsPin(&'a str),
+}
+
+impl Security<'_> {
+    /// Security type sent to the module.
+    pub const fn security_type(&self) -> SecurityType {
+        match self {
+            Self::Open => SecurityType::Open,
+            Self::Wep(_) => SecurityType::Wep,
+            Self::WepShared(_) => SecurityType::WepShared,
+            Self::WpaWpa2(_) => SecurityType::WpaWpa2,
+            Self::Wpa2Plus(_) => SecurityType::Wpa2Plus,
+            Self::Wpa3(_) => SecurityType::Wpa3,
+            Self::Enterprise(_) => SecurityType::WpaEnt,
+            Self::WpsPbc => SecurityType::WpsPbc,
+            Self::WpsPin(_) => SecurityType::WpsPin,
+        }
+    }
+
+    /// Check the credentials.
+    pub fn validate(&self) -> Result<(), CredentialError> {
+        match self {
+            Self::Open | Self::WpsPbc => Ok(()),
+            Self::WpsPin(pin) => check_wps_pin(pin),
+            Self::Wep(key) | Self::WepShared(key) => check_wep_key(key),
+            Self::WpaWpa2(key) | Self::Wpa2Plus(key) | Self::Wpa3(key) => {
+                check_passphrase(key)
+            }
+            Self::Enterprise(enterprise) => {
+                check_user(enterprise.password)?;
+                enterprise.user.map(check_user).transpose()?;
+                enterprise.anon_user.map(check_user).transpose()?;
+                enterprise.tls.validate()
+            }
+        }
+    }
+}
+
+/// Network to connect to or store as a profile.
+///
+/// Used with [`Calypso::wlan_connect`](crate::Calypso::wlan_connect) and
+/// [`Calypso::wlan_profile_add`](crate::Calypso::wlan_profile_add).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectParams<'a> {
+    ssid: &'a str,
+    bssid: Option<&'a str>,
+    security: Security<'a>,
+}
+
+impl<'a> ConnectParams<'a> {
+    /// Network with the given SSID and security.
+    pub const fn new(ssid: &'a str, security: Security<'a>) -> Self {
+        Self {
+            ssid,
+            bssid: None,
+            security,
+        }
+    }
+
+    /// Only connect to the access point with the given BSSID, e.g.
+    /// `aa:bb:cc:dd:ee:ff`.
+    pub const fn bssid(mut self, bssid: &'a str) -> Self {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// SSID of the network.
+    pub const fn ssid(&self) -> &'a str {
+        self.ssid
+    }
+
+    /// Security of the network.
+    pub const fn security(&self) -> &Security<'a> {
+        &self.security
+    }
+
+    /// Check the SSID, BSSID and credentials.
+    ///
+    /// The SSID may be left empty for WPS, the access point sends it.
+    pub fn validate(&self) -> Result<(), CredentialError> {
+        let wps =
+            matches!(self.security, Security::WpsPbc | Security::WpsPin(_));
+        if !(wps && self.ssid.is_empty()) {
+            check_ssid(self.ssid)?;
+        }
+        self.bssid.map(check_bssid).transpose()?;
+        self.security.validate()
+    }
+
+    /// Connect command for the network.
+    pub(crate) fn command(&self) -> Result<Connect<'a>, Error> {
+        self.validate()?;
+
+        let (key, enterprise) = match self.security {
+            Security::Wep(key)
+            | Security::WepShared(key)
+            | Security::WpaWpa2(key)
+            | Security::Wpa2Plus(key)
+            | Security::Wpa3(key)
+            | Security::WpsPin(key) => (key, None),
+            Security::Enterprise(enterprise) => {
+                (enterprise.password, Some(enterprise))
+            }
+            Security::Open | Security::WpsPbc => ("", None),
+        };
+
+        Ok(Connect {
+            ssid: Escaped::new(self.ssid, MAX_SSID_LENGTH)?,
+            bssid: Escaped::new(
+                self.bssid.unwrap_or_default(),
+                MAX_BSSID_LENGTH,
+            )?,
+            security_type: self.security.security_type().into(),
+            security_key: Escaped::new(key, MAX_SECURITY_KEY_LENGTH)?,
+            security_ext_user: Escaped::new(
+                enterprise.and_then(|e| e.user).unwrap_or_default(),
+                MAX_USER_LENGTH,
+            )?,
+            security_ext_anon_user: Escaped::new(
+                enterprise.and_then(|e| e.anon_user).unwrap_or_default(),
+                MAX_USER_LENGTH,
+            )?,
+            security_ext_eap_method: enterprise
+                .map(|e| e.method.into())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Profile command for the network.
+    pub(crate) fn profile(
+        &self,
+        priority: u8,
+    ) -> Result<ProfileAdd<'a>, Error> {
+        let connect = self.command()?;
+
+        Ok(ProfileAdd {
+            ssid: connect.ssid,
+            bssid: connect.bssid,
+            security_type: connect.security_type,
+            security_key: connect.security_key,
+            security_ext_user: connect.security_ext_user,
+            security_ext_anon_user: connect.security_ext_anon_user,
+            security_ext_eap_method: connect.security_ext_eap_method,
+            priority,
+        })
+    }
+}
+
+/// Check an SSID.
+pub fn check_ssid(ssid: &str) -> Result<(), CredentialError> {
+    if ssid.is_empty() || ssid.len() > MAX_SSID_LENGTH {
+        return Err(CredentialError::SsidLength(ssid.len()));
+    }
+
+    check_characters(ssid)
+}
+
+/// Check a BSSID, e.g. `aa:bb:cc:dd:ee:ff`.
+pub fn check_bssid(bssid: &str) -> Result<(), CredentialError> {
+    let mut octets = 0;
+    for octet in bssid.split(':') {
+        if octet.len() != 2 || !is_hex(octet) {
+            return Err(CredentialError::InvalidBssid);
+        }
+        octets += 1;
+    }
+
+    if octets != 6 {
+        return Err(CredentialError::InvalidBssid);
+    }
+
+    Ok(())
+}
+
+/// Check a WPA passphrase or a hex encoded 64 digit pre-shared key.
+pub fn check_passphrase(passphrase: &str) -> Result<(), CredentialError> {
+    match passphrase.len() {
+        PSK_HEX_LENGTH if is_hex(passphrase) => Ok(()),
+        PSK_HEX_LENGTH => Err(CredentialError::InvalidPsk),
+        8..=MAX_PASSPHRASE_LENGTH => {
+            match passphrase.chars().find(|c| !(' '..='~').contains(c)) {
+                Some(c) => Err(CredentialError::InvalidCharacter(c)),
+                None => check_characters(passphrase),
+            }
+        }
+        len => Err(CredentialError::PassphraseLength(len)),
+    }
+}
+
+/// Check a WEP key, given as ASCII characters or hex digits.
+pub fn check_wep_key(key: &str) -> Result<(), CredentialError> {
+    match key.len() {
+        5 | 13 => check_characters(key),
+        10 | 26 if is_hex(key) => Ok(()),
+        len => Err(CredentialError::WepKeyLength(len)),
+    }
+}
+
+/// Check an enterprise user name or password.
+pub fn check_user(user: &str) -> Result<(), CredentialError> {
+    if user.len() > MAX_USER_LENGTH {
+        return Err(CredentialError::UserLength(user.len()));
+    }
+
+    check_characters(user)
+}
+
+/// Check a WPS PIN of 4 digits, or 8 digits ending in a checksum digit.
+pub fn check_wps_pin(pin: &str) -> Result<(), CredentialError> {
+    if !pin.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(CredentialError::InvalidWpsPin);
+    }
+
+    match pin.len() {
+        4 => Ok(()),
+        8 => {
+            let value: u32 =
+                pin.parse().map_err(|_| CredentialError::InvalidWpsPin)?;
+            if wps_checksum(value / 10) == value % 10 {
+                Ok(())
+            } else {
+                Err(CredentialError::InvalidWpsPin)
+            }
+        }
+        _ => Err(CredentialError::InvalidWpsPin),
+    }
+}
+
+/// Check the certificates of an enterprise network and apply its server
+/// validation setting.
+pub(crate) async fn configure_enterprise<C: AtatClient>(
+    calypso: &mut Calypso<C>,
+    tls: &EnterpriseTls<'_>,
+) -> Result<(), Error> {
+    let files = [tls.ca, tls.certificate, tls.private_key];
+    for file in files.into_iter().flatten() {
+        match calypso.file_info(file.name(), 0).await {
+            Ok(_) => {}
+            Err(Error::Module(_)) => {
+                return Err(CredentialError::MissingCertificate.into())
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    calypso
+        .client
+        .send(&SetGeneral {
+            option: GeneralOption::DisableEntServerAuth.into(),
+            value: Escaped::new(if tls.skip_ca_verify { "1" } else { "0" }, 1)?,
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Wait for the outcome of a connection attempt.
+///
+/// Events received before the attempt are skipped, so the subscription has
+/// to be drained before connecting.
+pub(crate) async fn wait_connected<
+    const CAPACITY: usize,
+    const SUBSCRIBERS: usize,
+    const N: usize,
+>(
+    urcs: &mut UrcSubscription<'_, Urc<N>, CAPACITY, SUBSCRIBERS>,
+    timeout: Duration,
+) -> Result<Connection, Error> {
+    with_timeout(timeout, async {
+        loop {
+            match urcs.next_message_pure().await {
+                Urc::Wlan(Event::Connect(connection)) => return Ok(connection),
+                Urc::Wlan(Event::Disconnect { connection, reason }) => {
+                    return Err(
+                        ConnectError::from_event(&connection, reason).into()
+                    )
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .map_err(|_| Error::Timeout)?
+}
+
+/// Wait for a connection attempt to connect and acquire an IP address.
+///
+/// Events received before the attempt are skipped, so the subscription has
+/// to be drained before connecting.
+pub(crate) async fn wait_online<
+    const CAPACITY: usize,
+    const SUBSCRIBERS: usize,
+    const N: usize,
+>(
+    urcs: &mut UrcSubscription<'_, Urc<N>, CAPACITY, SUBSCRIBERS>,
+    timeout: Duration,
+) -> Result<Link, Error> {
+    let mut connection = None;
+    let mut ip = None;
+
+    let result = with_timeout(timeout, async {
+        loop {
+            match urcs.next_message_pure().await {
+                Urc::Wlan(Event::Connect(connected)) => {
+                    connection = Some(connected)
+                }
+                Urc::Wlan(Event::Disconnect {
+                    connection: disconnected,
+                    reason,
+                }) => {
+                    return Err(ConnectError::from_event(&disconnected, reason))
+                }
+                Urc::NetApp(netapp::Event::Ipv4Acquired(acquired)) => {
+                    ip = Some(acquired)
+                }
+                _ => {}
+            }
+
+            if let (Some(connection), Some(ip)) = (&connection, ip) {
+                return Ok(Link {
+                    connection: connection.clone(),
+                    ip,
+                });
+            }
+        }
+    })
+    .await;
+
+    match result {
+        Ok(link) => Ok(link?),
+        Err(_) if connection.is_some() => Err(ConnectError::DhcpTimeout.into()),
+        Err(_) => Err(Error::Timeout),
+    }
+}
+
+/// Reject control characters, which would end the AT command line.
+///
+/// Separators and quotes are escaped when the command is sent.
+fn check_characters(value: &str) -> Result<(), CredentialError> {
+    match value.chars().find(|c| c.is_control()) {
+        Some(c) => Err(CredentialError::InvalidCharacter(c)),
+        None => Ok(()),
+    }
+}
+
+fn is_hex(value: &str) -> bool {
+    value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{block_on, MockClient};
+    use atat::{AtatUrcChannel, UrcChannel};
+
+    fn connection(bssid: &str) -> Connection {
+        Connection {
+            ssid: "home".into(),
+            bssid: bssid.into(),
+        }
+    }
+
+    #[test]
+    fn wps_pin() {
+        assert_eq!(WpsPin::new(1234567).as_str(), "12345670");
+        assert_eq!(WpsPin::new(42).as_str(), "00000420");
+        assert_eq!(WpsPin::new(91234567).as_str(), "12345670");
+
+        for value in [0, 1, 1234567, 9999999, 7654321] {
+            assert_eq!(check_wps_pin(WpsPin::new(value).as_str()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn wps_pin_check() {
+        assert_eq!(check_wps_pin("1234"), Ok(()));
+        assert_eq!(check_wps_pin("12345670"), Ok(()));
+        for pin in ["12345671", "123", "12345", "1234567a", "+1234567"] {
+            assert_eq!(check_wps_pin(pin), Err(CredentialError::InvalidWpsPin));
+        }
+    }
+
+    #[test]
+    fn ssid() {
+        assert_eq!(check_ssid("home, sweet \"home\""), Ok(()));
+        assert_eq!(check_ssid(""), Err(CredentialError::SsidLength(0)));
+        assert_eq!(
+            check_ssid(&"a".repeat(33)),
+            Err(CredentialError::SsidLength(33))
+        );
+        assert_eq!(
+            check_ssid("line\r\nbreak"),
+            Err(CredentialError::InvalidCharacter('\r'))
+        );
+    }
+
+    #[test]
+    fn bssid() {
+        assert_eq!(check_bssid("aa:BB:cc:00:11:ff"), Ok(()));
+        for bssid in ["aa:bb:cc:dd:ee", "aa:bb:cc:dd:ee:ff:00", "aabbccddeeff"]
+        {
+            assert_eq!(check_bssid(bssid), Err(CredentialError::InvalidBssid));
+        }
+        assert_eq!(
+            check_bssid("aa:bb:cc:dd:ee:gg"),
+            Err(CredentialError::InvalidBssid)
+        );
+    }
+
+    #[test]
+    fn passphrase() {
+        assert_eq!(check_passphrase("12345678"), Ok(()));
+        assert_eq!(check_passphrase(&"x".repeat(63)), Ok(()));
+        assert_eq!(check_passphrase(&"0f".repeat(32)), Ok(()));
+        assert_eq!(
+            check_passphrase(&"x".repeat(64)),
+            Err(CredentialError::InvalidPsk)
+        );
+        assert_eq!(
+            check_passphrase("1234567"),
+            Err(CredentialError::PassphraseLength(7))
+        );
+        assert_eq!(
+            check_passphrase("pässword"),
+            Err(CredentialError::InvalidCharacter('ä'))
+        );
+    }
+
+    #[test]
+    fn wep_key() {
+        assert_eq!(check_wep_key("abcde"), Ok(()));
+        assert_eq!(check_wep_key("0123456789"), Ok(()));
+        assert_eq!(check_wep_key(&"ab".repeat(13)), Ok(()));
+        assert_eq!(
+            check_wep_key("012345678x"),
+            Err(CredentialError::WepKeyLength(10))
+        );
+        assert_eq!(check_wep_key("abc"), Err(CredentialError::WepKeyLength(3)));
+    }
+
+    #[test]
+    fn enterprise() {
+        let enterprise = |tls| {
+            Security::Enterprise(Enterprise {
+                method: SecurityEapType::Peap0Mschapv2,
+                user: Some("user"),
+                anon_user: None,
+                password: "secret",
+                tls,
+            })
+        };
+
+        let tls = EnterpriseTls {
+            ca: Some(CertificateFile::ENTERPRISE_CA),
+            ..EnterpriseTls::default()
+        };
+        assert_eq!(enterprise(tls).validate(), Ok(()));
+
+        let tls = EnterpriseTls {
+            ca: Some(CertificateFile::new("/cert/ca.der")),
+            ..EnterpriseTls::default()
+        };
+        assert_eq!(
+            enterprise(tls).validate(),
+            Err(CredentialError::CertificateFile)
+        );
+    }
+
+    #[test]
+    fn missing_certificates() {
+        let client = MockClient::default()
+            .respond(b"+filegetinfo:0,512,4096,0,0,0,0,4096,1")
+            .fail(atat::Error::CustomMessage(b"-11"[..].try_into().unwrap()));
+        let mut calypso = Calypso::new(client);
+        let tls = EnterpriseTls {
+            ca: Some(CertificateFile::ENTERPRISE_CA),
+            certificate: Some(CertificateFile::ENTERPRISE_CLIENT),
+            ..EnterpriseTls::default()
+        };
+
+        assert_eq!(
+            block_on(configure_enterprise(&mut calypso, &tls)),
+            Err(Error::Credentials(CredentialError::MissingCertificate))
+        );
+        assert_eq!(
+            calypso.client.sent[1],
+            "AT+fileGetInfo=/sys/cert/client.der,0\r\n"
+        );
+    }
+
+    #[test]
+    fn connect_params() {
+        assert_eq!(ConnectParams::new("", Security::WpsPbc).validate(), Ok(()));
+        assert_eq!(
+            ConnectParams::new("", Security::Open).validate(),
+            Err(CredentialError::SsidLength(0))
+        );
+        assert_eq!(
+            ConnectParams::new("home", Security::Open)
+                .bssid("aa:bb")
+                .validate(),
+            Err(CredentialError::InvalidBssid)
+        );
+        assert_eq!(
+            ConnectParams::new("home", Security::WpaWpa2("short")).validate(),
+            Err(CredentialError::PassphraseLength(5))
+        );
+    }
+
+    #[test]
+    fn connect_errors() {
+        let connected = connection("aa:bb:cc:dd:ee:ff");
+        let unknown = connection("00:00:00:00:00:00");
+
+        assert_eq!(
+            ConnectError::from_event(&connected, 15),
+            ConnectError::AuthFailed
+        );
+        assert_eq!(
+            ConnectError::from_event(&unknown, 15),
+            ConnectError::AuthFailed
+        );
+        assert_eq!(
+            ConnectError::from_event(&connected, 3),
+            ConnectError::Disconnected(3)
+        );
+        assert_eq!(
+            ConnectError::from_event(&unknown, 3),
+            ConnectError::ApNotFound
+        );
+        assert_eq!(
+            ConnectError::from_event(&connection(""), 200),
+            ConnectError::ApNotFound
+        );
+    }
+
+    #[test]
+    fn online() {
+        let channel = UrcChannel::<Urc, 4, 1>::new();
+        let mut urcs = channel.subscribe().unwrap();
+        let publisher = channel.publisher();
+        let ip = Ipv4Config {
+            address: [192, 168, 1, 20].into(),
+            gateway: [192, 168, 1, 1].into(),
+            dns: [192, 168, 1, 1].into(),
+        };
+        publisher.publish_immediate(Urc::Wlan(Event::Connect(connection(
+            "aa:bb:cc:dd:ee:ff",
+        ))));
+        publisher
+            .publish_immediate(Urc::NetApp(netapp::Event::Ipv4Acquired(ip)));
+
+        assert_eq!(
+            block_on(wait_online(&mut urcs, Duration::from_millis(100))),
+            Ok(Link {
+                connection: connection("aa:bb:cc:dd:ee:ff"),
+                ip,
+            })
+        );
+    }
+
+    #[test]
+    fn online_failures() {
+        let channel = UrcChannel::<Urc, 4, 1>::new();
+        let mut urcs = channel.subscribe().unwrap();
+        let publisher = channel.publisher();
+        let timeout = Duration::from_millis(10);
+
+        assert_eq!(
+            block_on(wait_online(&mut urcs, timeout)),
+            Err(Error::Timeout)
+        );
+
+        publisher.publish_immediate(Urc::Wlan(Event::Connect(connection(
+            "aa:bb:cc:dd:ee:ff",
+        ))));
+        assert_eq!(
+            block_on(wait_online(&mut urcs, timeout)),
+            Err(Error::Connect(ConnectError::DhcpTimeout))
+        );
+
+        publisher.publish_immediate(Urc::Wlan(Event::Disconnect {
+            connection: connection("aa:bb:cc:dd:ee:ff"),
+            reason: 2,
+        }));
+        assert_eq!(
+            block_on(wait_online(&mut urcs, timeout)),
+            Err(Error::Connect(ConnectError::AuthFailed))
+        );
+    }
+}